@@ -3,15 +3,21 @@ use termfmt::TermFmtExt;
 
 use crate::config::Config;
 use crate::output::OutputFmt;
+use crate::vocabulary::Vocabulary;
 
 mod carlender;
 mod clean;
 mod code;
 mod commit;
+mod filter;
 mod generate;
+mod graph;
 mod list;
 mod persons;
+mod reindex;
 mod search;
+mod sort;
+mod tags;
 mod todo;
 
 pub fn list() -> impl IntoIterator<Item = Command> {
@@ -21,21 +27,25 @@ pub fn list() -> impl IntoIterator<Item = Command> {
         code::command(),
         commit::command(),
         generate::command(),
+        graph::command(),
         list::command(),
         persons::command(),
+        reindex::command(),
         search::command(),
+        tags::command(),
         todo::command(),
     ]
 }
 
-pub fn run(command: ArgMatches) {
-    let config = Config::load();
+pub fn run(command: ArgMatches) -> eyre::Result<()> {
+    let config = Config::load()?;
+    Vocabulary::install(&config)?;
     let mut term = command.termfmt(&config);
 
     let Some((name, command)) = command.subcommand() else {
         term.error("please provide a command!");
         term.info("run 'scatternotes --help' for more info");
-        return;
+        return Ok(());
     };
 
     match name {
@@ -44,13 +54,17 @@ pub fn run(command: ArgMatches) {
         code::NAME => code::run(command, &mut term, &config),
         commit::NAME => commit::run(&mut term, &config),
         generate::NAME => generate::run(command, &mut term, &config),
+        graph::NAME => graph::run(command, &mut term, &config),
         list::NAME => list::run(command, &mut term, &config),
         persons::NAME => persons::run(command, &mut term, &config),
+        reindex::NAME => reindex::run(&mut term, &config),
         search::NAME => search::run(command, &mut term, &config),
+        tags::NAME => tags::run(command, &mut term, &config),
         todo::NAME => todo::run(command, &mut term, &config),
         _ => term.error(format_args!("command not implemented: {}", name)),
     }
 
     term.flush().unwrap();
     term.end();
+    Ok(())
 }
@@ -39,7 +39,8 @@ pub fn run(term: &mut Term, config: &Config) {
                 term.error("could not get date of note!");
                 continue;
             };
-            let new_note = generator.generate_with_date(date, config);
+            let directory = note.path().parent().unwrap_or(config.path());
+            let new_note = generator.generate_in(directory, date);
             if let Err(error) = fs::rename(note.path(), &new_note) {
                 term.error(error);
                 continue;
@@ -0,0 +1,53 @@
+use chrono::Local;
+use clap::{Arg, ArgAction, ArgMatches};
+
+use crate::date::{DateFilter, DateRange};
+
+pub const ARG_SINCE: &str = "since";
+pub const ARG_UNTIL: &str = "until";
+pub const ARG_ON: &str = "on";
+pub const ARG_DATES: &str = "dates";
+
+const DATE_HELP: &str = "(iso dates, 7d, 2w, 3m, 1y, today, yesterday, this-week, last-month, ...)";
+
+pub fn args() -> [Arg; 4] {
+    [
+        Arg::new(ARG_SINCE).long(ARG_SINCE).help(format!(
+            "only notes dated on or after the date {}",
+            DATE_HELP
+        )),
+        Arg::new(ARG_UNTIL).long(ARG_UNTIL).help(format!(
+            "only notes dated on or before the date {}",
+            DATE_HELP
+        )),
+        Arg::new(ARG_ON).long(ARG_ON).help(format!(
+            "only notes dated on the day or in the period {}",
+            DATE_HELP
+        )),
+        Arg::new(ARG_DATES)
+            .short('d')
+            .long(ARG_DATES)
+            .action(ArgAction::SetTrue)
+            .help("display the dates of the notes"),
+    ]
+}
+
+pub fn date_filter(command: &ArgMatches) -> eyre::Result<DateFilter> {
+    let today = Local::now().date_naive();
+    let mut filter = DateFilter::default();
+    if let Some(since) = command.get_one::<String>(ARG_SINCE) {
+        filter.since(DateRange::parse(since, today)?);
+    }
+    if let Some(until) = command.get_one::<String>(ARG_UNTIL) {
+        filter.until(DateRange::parse(until, today)?);
+    }
+    if let Some(on) = command.get_one::<String>(ARG_ON) {
+        filter.on(DateRange::parse(on, today)?);
+    }
+    Ok(filter)
+}
+
+/// The dates are displayed if requested or if the notes are filtered by date.
+pub fn show_dates(command: &ArgMatches, filter: &DateFilter) -> bool {
+    command.get_flag(ARG_DATES) || filter.is_active()
+}
@@ -0,0 +1,105 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+
+use crate::config::Config;
+use crate::graph::{Graph, GraphFormat};
+use crate::note::Note;
+use crate::output::{OutputFmt, Term};
+
+pub const NAME: &str = "graph";
+
+pub const CMD_TAGS: &str = "tags";
+
+pub const ARG_FORMAT: &str = "format";
+pub const ARG_MIN_WEIGHT: &str = "min-weight";
+pub const ARG_OUTPUT: &str = "output";
+pub const ARG_PERSONS: &str = "persons";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .subcommands([Command::new(CMD_TAGS)
+            .args(args())
+            .arg(
+                Arg::new(ARG_PERSONS)
+                    .short('p')
+                    .long(ARG_PERSONS)
+                    .action(ArgAction::SetTrue)
+                    .help("include the persons as nodes"),
+            )
+            .about("export the tags used together in the same notes")])
+        .subcommand_required(true)
+        .about("export graphs of your notes")
+}
+
+/// The options to render and prune a graph.
+pub fn args() -> [Arg; 3] {
+    [
+        Arg::new(ARG_FORMAT)
+            .short('f')
+            .long(ARG_FORMAT)
+            .value_parser(value_parser!(GraphFormat))
+            .default_value("dot")
+            .help("the format of the graph"),
+        Arg::new(ARG_MIN_WEIGHT)
+            .short('w')
+            .long(ARG_MIN_WEIGHT)
+            .value_parser(value_parser!(usize))
+            .default_value("1")
+            .help("leave out edges shared by fewer notes"),
+        Arg::new(ARG_OUTPUT)
+            .short('o')
+            .long(ARG_OUTPUT)
+            .value_parser(value_parser!(PathBuf))
+            .help("write the graph to a file instead of printing it"),
+    ]
+}
+
+pub fn run(command: &ArgMatches, term: &mut Term, config: &Config) {
+    match command.subcommand().unwrap() {
+        (CMD_TAGS, command) => run_tags(command, term, config),
+        (name, _) => term.error(format_args!(
+            "command '{} {}' is not implemented",
+            NAME, name
+        )),
+    }
+}
+
+/// Remove the edges below the minimum weight given on the command line.
+pub fn prune(graph: &mut Graph, command: &ArgMatches) {
+    graph.prune(*command.get_one::<usize>(ARG_MIN_WEIGHT).unwrap());
+}
+
+/// Prune the graph and print it or write it to the output file.
+pub fn output(mut graph: Graph, command: &ArgMatches, term: &mut Term) {
+    prune(&mut graph, command);
+    let format = *command.get_one::<GraphFormat>(ARG_FORMAT).unwrap();
+    let Some(path) = command.get_one::<PathBuf>(ARG_OUTPUT) else {
+        term.graph(&graph, format);
+        return;
+    };
+    let result = graph
+        .render(format)
+        .and_then(|content| Ok(fs::write(path, content)?));
+    match result {
+        Ok(()) => term.file(path),
+        Err(error) => term.file_error(path, error),
+    }
+}
+
+fn run_tags(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let Ok(notes) = Note::all_notes(config) else {
+        term.error("could not read notes directory!");
+        return;
+    };
+    let with_persons = command.get_flag(ARG_PERSONS);
+    let graph = Graph::co_occurrence(notes.map(|note| {
+        note.tags()
+            .iter()
+            .filter(|tag| with_persons || !tag.is_person())
+            .map(|tag| tag.to_string())
+            .collect::<Vec<_>>()
+    }));
+    output(graph, command, term);
+}
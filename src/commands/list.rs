@@ -4,6 +4,9 @@ use crate::config::Config;
 use crate::note::Note;
 use crate::output::{OutputFmt, Term};
 
+use super::filter;
+use super::sort::{self, Sorting};
+
 pub const NAME: &'static str = "list";
 pub const ARG_TAGS: &'static str = "tags";
 
@@ -14,18 +17,35 @@ pub fn command() -> Command {
             .long(ARG_TAGS)
             .action(ArgAction::SetTrue)
             .help("display the tags of the notes")])
+        .args(filter::args())
+        .args(sort::args())
         .about("list all possible note files")
 }
 
 pub fn run(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let filter = match filter::date_filter(command) {
+        Ok(filter) => filter,
+        Err(error) => {
+            term.error(error);
+            term.end();
+            return;
+        }
+    };
     let Ok(notes) = Note::all_notes(config) else {
         term.file_error(config.path(), "could not read notes directory!");
         term.end();
         return;
     };
     let show_tags = command.get_flag(ARG_TAGS);
+    let show_dates = filter::show_dates(command, &filter);
+    let sorting = Sorting::from_matches(command);
 
-    for note in notes {
-        term.list(&note, show_tags);
+    let mut notes: Vec<Note> = notes
+        .filter(|note| filter.matches(note.date(config)))
+        .collect();
+    sorting.sort(&mut notes, config, None, |note| note);
+    for note in notes.iter().take(sorting.limit()) {
+        let date = note.date(config).filter(|_| show_dates);
+        term.list(note, show_tags, date, None);
     }
 }
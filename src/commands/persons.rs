@@ -1,26 +1,98 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
 
-use clap::{ArgMatches, Command};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
 use itertools::Itertools;
 
 use crate::config::Config;
+use crate::edit::TagRename;
+use crate::graph::Graph;
 use crate::note::Note;
 use crate::output::{OutputFmt, Term};
-use crate::person::Person;
+use crate::person::{Person, Profiles};
+use crate::tag::Tag;
+use crate::todo::Todo;
+use crate::vcard;
+
+use super::{filter, graph, tags};
 
 pub const NAME: &str = "persons";
 
 pub const CMD_INDEX: &str = "index";
 pub const CMD_LIST: &str = "list";
 pub const CMD_CLEAN: &str = "clean";
+pub const CMD_SHOW: &str = "show";
+pub const CMD_TIMELINE: &str = "timeline";
+pub const CMD_GRAPH: &str = "graph";
+pub const CMD_EXPORT: &str = "export";
+pub const CMD_IMPORT: &str = "import";
+
+pub const ARG_NAME: &str = "name";
+pub const ARG_CONNECTIONS: &str = "connections";
+pub const ARG_VCARD: &str = "vcard";
+pub const ARG_OUTPUT: &str = "output";
+pub const ARG_FILE: &str = "file";
 
 pub fn command() -> Command {
     Command::new(NAME)
         .subcommands([
             Command::new(CMD_INDEX).about("create index for persons in notes"),
             Command::new(CMD_LIST).about("list persons from the index"),
-            Command::new(CMD_CLEAN).about("clean the persons in notes"),
+            Command::new(CMD_CLEAN)
+                .arg(tags::dry_run_arg())
+                .about("write all mentions of a person with the same handle"),
+            Command::new(CMD_SHOW)
+                .arg(
+                    Arg::new(ARG_NAME)
+                        .required(true)
+                        .help("the handle or an alias of the person"),
+                )
+                .about("show the profile of a person"),
+            Command::new(CMD_TIMELINE)
+                .arg(
+                    Arg::new(ARG_NAME)
+                        .required(true)
+                        .help("the handle or an alias of the person"),
+                )
+                .args(filter::args())
+                .about("show the notes and open todos mentioning a person by date"),
+            Command::new(CMD_GRAPH)
+                .args(graph::args())
+                .arg(
+                    Arg::new(ARG_CONNECTIONS)
+                        .short('c')
+                        .long(ARG_CONNECTIONS)
+                        .action(ArgAction::SetTrue)
+                        .help("list the persons each person is most connected with"),
+                )
+                .about("export the persons mentioned together in the same notes"),
+            Command::new(CMD_EXPORT)
+                .arg(
+                    Arg::new(ARG_VCARD)
+                        .long(ARG_VCARD)
+                        .action(ArgAction::SetTrue)
+                        .required(true)
+                        .help("export the profiles as vcards"),
+                )
+                .arg(
+                    Arg::new(ARG_OUTPUT)
+                        .short('o')
+                        .long(ARG_OUTPUT)
+                        .value_parser(value_parser!(PathBuf))
+                        .default_value("persons.vcf")
+                        .help("the file to write the persons to"),
+                )
+                .about("export the profiles of the persons"),
+            Command::new(CMD_IMPORT)
+                .arg(
+                    Arg::new(ARG_FILE)
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf))
+                        .help("the vcf file to import"),
+                )
+                .about("add or update persons from a vcf file"),
         ])
         .subcommand_required(true)
         .about("manage the persons in your notes")
@@ -30,7 +102,10 @@ pub fn run(command: &ArgMatches, term: &mut Term, config: &Config) {
     match command.subcommand().unwrap() {
         (CMD_INDEX, _) => {
             let persons = Person::search_all_persons(config).unwrap();
-            Person::save_persons(config, &persons);
+            if let Err(error) = Person::save_persons(config, &persons) {
+                term.error(error);
+                return;
+            }
             term.headline("FOUND PERSONS");
             if persons.is_empty() {
                 term.info("no persons found");
@@ -40,7 +115,7 @@ pub fn run(command: &ArgMatches, term: &mut Term, config: &Config) {
         }
         (CMD_LIST, _) => {
             let Ok(persons) = Person::load_all_persons(config) else {
-                term.error("no 'persons.txt' found");
+                term.error("no 'persons.json' found");
                 term.info("run 'scatternotes persons index' to generate the file");
                 return;
             };
@@ -51,9 +126,181 @@ pub fn run(command: &ArgMatches, term: &mut Term, config: &Config) {
                 term.persons(&persons);
             }
         }
+        (CMD_CLEAN, command) => run_clean(command, term, config),
+        (CMD_TIMELINE, command) => run_timeline(command, term, config),
+        (CMD_GRAPH, command) => run_graph(command, term, config),
+        (CMD_EXPORT, command) => run_export(command, term, config),
+        (CMD_IMPORT, command) => run_import(command, term, config),
+        (CMD_SHOW, command) => {
+            let name: &String = command.get_one(ARG_NAME).unwrap();
+            let profiles = match Profiles::load(config) {
+                Ok(profiles) => profiles,
+                Err(error) => return term.error(error),
+            };
+            let Some((handle, profile)) = profiles.get(name) else {
+                term.error(format_args!("no person named '{}'", name));
+                term.info("run 'scatternotes persons index' to add the persons of your notes");
+                return;
+            };
+            term.profile(handle, profile);
+        }
         (name, _) => term.error(format_args!(
             "command '{} {}' is not implemented",
             NAME, name
         )),
     }
 }
+
+fn run_clean(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let profiles = match Profiles::load(config) {
+        Ok(profiles) => profiles,
+        Err(error) => return term.error(error),
+    };
+    let Ok(notes) = Note::all_notes(config) else {
+        term.error("could not read notes directory!");
+        return;
+    };
+    let notes: Vec<Note> = notes.collect();
+    let variants = profiles.variants(notes.iter().map(Note::content));
+
+    term.headline("PERSON VARIANTS");
+    if variants.is_empty() {
+        term.info("all persons are written with their handle");
+        return;
+    }
+    for (handle, names) in &variants {
+        term.person_variants(handle, names);
+    }
+    let rename = TagRename::persons(
+        variants
+            .iter()
+            .flat_map(|(handle, names)| names.keys().map(|name| (name.clone(), handle.clone()))),
+    );
+    let dry_run = command.get_flag(tags::ARG_DRY_RUN);
+    tags::run_rename(&rename, dry_run, term, config);
+    if dry_run {
+        return;
+    }
+    let result = Person::search_all_persons(config)
+        .and_then(|persons| Person::save_persons(config, &persons));
+    if let Err(error) = result {
+        term.error(error);
+    }
+}
+
+fn run_timeline(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let filter = match filter::date_filter(command) {
+        Ok(filter) => filter,
+        Err(error) => return term.error(error),
+    };
+    let name: &String = command.get_one(ARG_NAME).unwrap();
+    let handle = match Profiles::load(config)
+        .ok()
+        .and_then(|profiles| Some(profiles.get(name)?.0.to_owned()))
+    {
+        Some(handle) => handle,
+        None => name.trim_start_matches('@').to_lowercase(),
+    };
+    let is_person = |tag: &Tag| matches!(tag, Tag::Person(person) if person.handle() == handle);
+    let Ok(notes) = Note::all_notes(config) else {
+        term.error("could not read notes directory!");
+        return;
+    };
+    let mut notes: Vec<Note> = notes
+        .filter(|note| note.tags().iter().any(is_person))
+        .filter(|note| filter.matches(note.date(config)))
+        .collect();
+    notes.sort_by_key(|note| (note.date(config).is_none(), note.date(config)));
+
+    term.headline(format_args!("TIMELINE OF @{}", handle));
+    if notes.is_empty() {
+        term.info(format_args!("no notes mention @{}", handle));
+        return;
+    }
+    for note in &notes {
+        let mut parts = Vec::new();
+        let mut todos = Vec::new();
+        for part in note
+            .parts()
+            .filter(|part| Tag::all(part).iter().any(is_person))
+        {
+            match Todo::parse_str(part) {
+                Some(todo) if !todo.is_done() => todos.push(part),
+                _ => parts.push(part),
+            }
+        }
+        term.timeline(note.path(), note.date(config), &parts, &todos);
+    }
+}
+
+fn run_graph(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let Ok(notes) = Note::all_notes(config) else {
+        term.error("could not read notes directory!");
+        return;
+    };
+    let profiles = Profiles::load(config).unwrap_or_default();
+    let mut graph = Graph::co_occurrence(notes.map(|note| {
+        note.persons()
+            .map(|person| match profiles.get(person.name()) {
+                Some((handle, _)) => format!("@{}", handle),
+                None => format!("@{}", person.handle()),
+            })
+            .collect::<Vec<_>>()
+    }));
+    if !command.get_flag(ARG_CONNECTIONS) {
+        graph::output(graph, command, term);
+        return;
+    }
+    graph::prune(&mut graph, command);
+    term.headline("MOST CONNECTED WITH");
+    for (person, _) in graph.nodes() {
+        let neighbours: Vec<(&str, usize)> = graph.neighbours(person).collect();
+        term.connections(person, &neighbours);
+    }
+}
+
+fn run_export(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let path: &PathBuf = command.get_one(ARG_OUTPUT).unwrap();
+    let profiles = match Profiles::load(config) {
+        Ok(profiles) => profiles,
+        Err(error) => return term.error(error),
+    };
+    if profiles.is_empty() {
+        term.error("no persons found in 'persons.json'");
+        term.info("run 'scatternotes persons index' to generate the file");
+        return;
+    }
+    match fs::write(path, vcard::export(&profiles)) {
+        Ok(()) => term.file(path),
+        Err(error) => term.file_error(path, error),
+    }
+}
+
+fn run_import(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let path: &PathBuf = command.get_one(ARG_FILE).unwrap();
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(error) => return term.file_error(path, error),
+    };
+    let persons = vcard::import(&content);
+    if persons.is_empty() {
+        term.info("no persons found");
+        return;
+    }
+    let mut profiles = match Profiles::load(config) {
+        Ok(profiles) => profiles,
+        Err(error) => return term.error(error),
+    };
+    let (added, updated) = profiles.import(persons);
+    if let Err(error) = profiles.save(config) {
+        term.error(error);
+        return;
+    }
+    term.headline("IMPORTED PERSONS");
+    for handle in added {
+        term.info(format_args!("added @{}", handle));
+    }
+    for handle in updated {
+        term.info(format_args!("updated @{}", handle));
+    }
+}
@@ -0,0 +1,30 @@
+use crate::config::Config;
+use crate::index::Index;
+use crate::output::{OutputFmt, Term};
+
+pub const NAME: &str = "reindex";
+
+pub fn command() -> clap::Command {
+    clap::Command::new(NAME).about("rebuild the index of the notes in the meta directory")
+}
+
+pub fn run(term: &mut Term, config: &Config) {
+    term.headline("REINDEX NOTES");
+
+    let mut index = Index::new();
+    let notes = match index.update(config) {
+        Ok(notes) => notes,
+        Err(error) => {
+            term.error(error);
+            return;
+        }
+    };
+    for path in index.unreadable() {
+        term.file_error(path, "could not read the note");
+    }
+
+    match notes.len() {
+        1 => term.info("indexed one note!"),
+        count => term.info(format_args!("indexed {} notes!", count)),
+    }
+}
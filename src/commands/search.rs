@@ -1,44 +1,307 @@
-use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::iter::once;
+
+use clap::parser::ValueSource;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use itertools::Itertools;
 
 use crate::config::Config;
 use crate::note::Note;
 use crate::output::{OutputFmt, Term};
+use crate::query::{MatchMode, Query};
+use crate::rank::{self, rank};
+use crate::saved::{SavedSearch, SavedSearches};
+
+use super::sort::{self, Sorting};
+use super::{filter, todo};
 
 pub const NAME: &'static str = "search";
 
 pub const ARG_QUERIES: &'static str = "queries";
 pub const ARG_TAGS: &'static str = "tags";
+pub const ARG_MATCH: &str = "match";
+pub const ARG_TEXT: &str = "text";
+pub const ARG_CONTEXT: &str = "context";
+pub const ARG_TABLE: &str = "table";
+pub const ARG_NAME: &str = "name";
+pub const ARG_ARGS: &str = "args";
+
+pub const CMD_SAVE: &str = "save";
+pub const CMD_RUN: &str = "run";
+pub const CMD_SAVED: &str = "saved";
 
 pub fn command() -> Command {
+    search_command()
+        .subcommands([
+            Command::new(CMD_SAVE)
+                .args([
+                    Arg::new(ARG_NAME)
+                        .required(true)
+                        .help("the name of the saved search"),
+                    Arg::new(ARG_ARGS)
+                        .num_args(1..)
+                        .trailing_var_arg(true)
+                        .allow_hyphen_values(true)
+                        .help("the queries and options of the search"),
+                ])
+                .about("save a search under a name"),
+            Command::new(CMD_RUN)
+                .arg(
+                    Arg::new(ARG_NAME)
+                        .required(true)
+                        .help("the name of the saved search"),
+                )
+                .args(search_command().get_arguments().cloned())
+                .about("run a saved search (additional queries and options are added)"),
+            Command::new(CMD_SAVED).about("list the saved searches"),
+        ])
+        .args_conflicts_with_subcommands(true)
+}
+
+fn search_command() -> Command {
     Command::new(NAME)
         .args([
             Arg::new(ARG_QUERIES)
                 .num_args(1..)
-                .help("the tags to search for. (the tags are additive)"),
+                .help("the query to search for. (supports AND, OR, NOT, parentheses and quotes)"),
             Arg::new(ARG_TAGS)
                 .long(ARG_TAGS)
                 .action(ArgAction::SetTrue)
                 .help("display the tags of the notes, which matched the search parameters"),
+            match_arg(),
+            Arg::new(ARG_TEXT)
+                .long(ARG_TEXT)
+                .action(ArgAction::SetTrue)
+                .help("search the content of the notes and rank them by relevance"),
+            Arg::new(ARG_CONTEXT)
+                .short('c')
+                .long(ARG_CONTEXT)
+                .action(ArgAction::SetTrue)
+                .help("display the parts of the notes, which matched the search parameters"),
+            Arg::new(ARG_TABLE)
+                .long(ARG_TABLE)
+                .value_name("keys")
+                .value_delimiter(',')
+                .help("display the notes as a table with the values of the given #key=value tags"),
         ])
+        .args(filter::args())
+        .args(sort::args())
+        .args_override_self(true)
         .about("search for notes using tags")
 }
 
+pub fn match_arg() -> Arg {
+    Arg::new(ARG_MATCH)
+        .short('m')
+        .long(ARG_MATCH)
+        .value_parser(value_parser!(MatchMode))
+        .help("how the tags are compared to the query (the config default if not given)")
+}
+
+pub fn match_mode(command: &ArgMatches, config: &Config) -> MatchMode {
+    command
+        .get_one::<MatchMode>(ARG_MATCH)
+        .copied()
+        .unwrap_or(config.match_mode())
+}
+
+/// Split the arguments given on the command line into queries and options.
+pub fn command_line_args(definition: &Command, command: &ArgMatches, skip: &[&str]) -> SavedSearch {
+    let mut search = SavedSearch::default();
+    for arg in definition.get_arguments() {
+        let id = arg.get_id().as_str();
+        if skip.contains(&id) || command.value_source(id) != Some(ValueSource::CommandLine) {
+            continue;
+        }
+        let values = command
+            .get_raw(id)
+            .into_iter()
+            .flatten()
+            .map(|value| value.to_string_lossy().into_owned());
+        if arg.is_positional() {
+            search.queries.extend(values);
+        } else if arg.get_action().takes_values() {
+            for value in values {
+                search.options.push(format!("--{}", id));
+                search.options.push(value);
+            }
+        } else {
+            search.options.push(format!("--{}", id));
+        }
+    }
+    search
+}
+
+/// Parse the saved search with the given command. Options not supported by
+/// the command are left out.
+pub fn parse_saved(definition: Command, search: &SavedSearch) -> Result<ArgMatches, clap::Error> {
+    let mut options = Vec::new();
+    let mut iter = search.options.iter().peekable();
+    while let Some(option) = iter.next() {
+        let id = option.trim_start_matches('-');
+        let takes_value = iter.peek().is_some_and(|next| !next.starts_with("--"));
+        if definition.get_arguments().any(|arg| arg.get_id() == id) {
+            options.push(option);
+            if takes_value {
+                options.extend(iter.next());
+            }
+        } else if takes_value {
+            iter.next();
+        }
+    }
+    let name = definition.get_name().to_owned();
+    definition.try_get_matches_from(once(&name).chain(&search.queries).chain(options))
+}
+
 pub fn run(command: &ArgMatches, term: &mut Term, config: &Config) {
-    let Some(tags) = command.get_many::<String>(ARG_QUERIES) else {
+    match command.subcommand() {
+        Some((CMD_SAVE, command)) => run_save(command, term, config),
+        Some((CMD_RUN, command)) => run_saved(command, term, config),
+        Some((CMD_SAVED, _)) => run_list_saved(term, config),
+        Some((name, _)) => term.error(format_args!(
+            "command '{} {}' is not implemented",
+            NAME, name
+        )),
+        None => run_search(command, term, config),
+    }
+}
+
+fn run_save(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let name: &String = command.get_one(ARG_NAME).unwrap();
+    let args = command.get_many::<String>(ARG_ARGS).unwrap_or_default();
+    let definition = search_command().arg(todo::done_arg());
+    let matches = match definition
+        .clone()
+        .try_get_matches_from(once(NAME).chain(args.map(String::as_str)))
+    {
+        Ok(matches) => matches,
+        Err(error) => return term.error(error.render()),
+    };
+    let search = command_line_args(&definition, &matches, &[]);
+
+    let mut searches = match SavedSearches::load(config) {
+        Ok(searches) => searches,
+        Err(error) => return term.error(error),
+    };
+    searches.insert(name.to_owned(), search);
+    if let Err(error) = searches.save(config) {
+        return term.error(error);
+    }
+    term.info(format_args!("saved search '{}'", name));
+}
+
+fn run_saved(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let name: &String = command.get_one(ARG_NAME).unwrap();
+    let Some(mut search) = SavedSearches::load(config)
+        .ok()
+        .and_then(|searches| searches.get(name).cloned())
+    else {
+        term.error(format_args!("no saved search named '{}'", name));
+        term.info("run 'scatternotes search saved' to list the saved searches");
+        return;
+    };
+    search.extend(command_line_args(&search_command(), command, &[ARG_NAME]));
+    match parse_saved(search_command(), &search) {
+        Ok(matches) => run_search(&matches, term, config),
+        Err(error) => term.error(error.render()),
+    }
+}
+
+fn run_list_saved(term: &mut Term, config: &Config) {
+    let searches = match SavedSearches::load(config) {
+        Ok(searches) => searches,
+        Err(error) => return term.error(error),
+    };
+    term.headline("SAVED SEARCHES");
+    let mut empty = true;
+    for (name, search) in searches.iter() {
+        empty = false;
+        term.saved_search(name, search);
+    }
+    if empty {
+        term.info("no saved searches");
+    }
+}
+
+fn run_search(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let Some(mut queries) = command.get_many::<String>(ARG_QUERIES) else {
         term.error("please provide tags to search by!");
         term.end();
         return;
     };
+    let filter = match filter::date_filter(command) {
+        Ok(filter) => filter,
+        Err(error) => {
+            term.error(error);
+            term.end();
+            return;
+        }
+    };
+    let show_tags = command.get_flag(ARG_TAGS);
+    let show_context = command.get_flag(ARG_CONTEXT);
+    let show_dates = filter::show_dates(command, &filter);
+    let sorting = Sorting::from_matches(command);
+    let table: Option<Vec<String>> = command
+        .get_many::<String>(ARG_TABLE)
+        .map(|keys| keys.cloned().collect());
 
-    let queries: Vec<_> = tags.into_iter().collect();
-    let Ok(notes) = Note::search(config, queries.as_slice()) else {
+    if command.get_flag(ARG_TEXT) {
+        let Ok(notes) = Note::all_notes(config) else {
+            term.error("could not read notes directory!");
+            term.end();
+            return;
+        };
+        let text = queries.join(" ");
+        let notes = notes.filter(|note| filter.matches(note.date(config)));
+        let mut ranked = rank(notes, &text);
+        sorting.sort(&mut ranked, config, None, |(note, _)| note);
+        if let Some(keys) = table {
+            let notes: Vec<&Note> = ranked
+                .iter()
+                .map(|(note, _)| note)
+                .take(sorting.limit())
+                .collect();
+            term.attribute_table(&keys, &notes);
+            return;
+        }
+        for (note, score) in ranked.iter().take(sorting.limit()) {
+            let date = note.date(config).filter(|_| show_dates);
+            term.list(note, show_tags, date, Some(*score));
+            if show_context {
+                term.snippets(&rank::matching_parts(note, &text));
+            }
+        }
+        return;
+    }
+
+    let query = match Query::parse(&queries.join(" ")) {
+        Ok(query) => query,
+        Err(error) => {
+            term.error(error);
+            term.end();
+            return;
+        }
+    };
+    let mode = match_mode(command, config);
+    let Ok(notes) = Note::search(config, &query, mode) else {
         term.error("could not read notes directory!");
         term.end();
         return;
     };
 
-    let show_tags = command.get_flag(ARG_TAGS);
-    for note in notes {
-        term.list(&note, show_tags);
+    let mut notes: Vec<Note> = notes
+        .filter(|note| filter.matches(note.date(config)))
+        .collect();
+    sorting.sort(&mut notes, config, Some((&query, mode)), |note| note);
+    if let Some(keys) = table {
+        let notes: Vec<&Note> = notes.iter().take(sorting.limit()).collect();
+        term.attribute_table(&keys, &notes);
+        return;
+    }
+    for note in notes.iter().take(sorting.limit()) {
+        let date = note.date(config).filter(|_| show_dates);
+        term.list(note, show_tags, date, None);
+        if show_context {
+            term.snippets(&query.matching_parts(note, mode));
+        }
     }
 }
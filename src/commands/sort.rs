@@ -0,0 +1,93 @@
+use std::fs;
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, ValueEnum};
+
+use crate::config::Config;
+use crate::note::Note;
+use crate::query::{MatchMode, Query};
+
+pub const ARG_SORT: &str = "sort";
+pub const ARG_REVERSE: &str = "reverse";
+pub const ARG_LIMIT: &str = "limit";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// the date of the note
+    Date,
+    /// the file name of the note
+    Name,
+    /// the last modification time of the note
+    Modified,
+    /// the number of tags of the note
+    Tags,
+    /// the number of tags matching the search query
+    Matches,
+}
+
+pub struct Sorting {
+    key: Option<SortKey>,
+    reverse: bool,
+    limit: Option<usize>,
+}
+
+pub fn args() -> [Arg; 3] {
+    [
+        Arg::new(ARG_SORT)
+            .short('s')
+            .long(ARG_SORT)
+            .value_parser(value_parser!(SortKey))
+            .help("sort the results in ascending order"),
+        Arg::new(ARG_REVERSE)
+            .short('r')
+            .long(ARG_REVERSE)
+            .action(ArgAction::SetTrue)
+            .help("reverse the order of the results"),
+        Arg::new(ARG_LIMIT)
+            .short('l')
+            .long(ARG_LIMIT)
+            .value_parser(value_parser!(usize))
+            .help("the maximum number of results"),
+    ]
+}
+
+impl Sorting {
+    pub fn from_matches(command: &ArgMatches) -> Self {
+        Self {
+            key: command.get_one::<SortKey>(ARG_SORT).copied(),
+            reverse: command.get_flag(ARG_REVERSE),
+            limit: command.get_one::<usize>(ARG_LIMIT).copied(),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(usize::MAX)
+    }
+
+    /// Sort the items by the note they belong to. The search query is used to
+    /// count the matching tags.
+    pub fn sort<T>(
+        &self,
+        items: &mut [T],
+        config: &Config,
+        query: Option<(&Query, MatchMode)>,
+        note: impl Fn(&T) -> &Note,
+    ) {
+        match self.key {
+            Some(SortKey::Date) => items.sort_by_cached_key(|item| note(item).date(config)),
+            Some(SortKey::Name) => items.sort_by_cached_key(|item| note(item).name().to_owned()),
+            Some(SortKey::Modified) => items.sort_by_cached_key(|item| {
+                fs::metadata(note(item).path())
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            }),
+            Some(SortKey::Tags) => items.sort_by_cached_key(|item| note(item).tags().len()),
+            Some(SortKey::Matches) => items.sort_by_cached_key(|item| {
+                query.map_or(0, |(query, mode)| query.count_matches(note(item), mode))
+            }),
+            None => {}
+        }
+        if self.reverse {
+            items.reverse();
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+
+use crate::config::Config;
+use crate::edit::{LineChange, TagRename};
+use crate::lint::TagGroup;
+use crate::note::Note;
+use crate::output::{OutputFmt, Term};
+use crate::stats::TagStats;
+use crate::tree::TagTree;
+
+pub const NAME: &str = "tags";
+
+pub const CMD_LIST: &str = "list";
+pub const CMD_TREE: &str = "tree";
+pub const CMD_RENAME: &str = "rename";
+pub const CMD_MERGE: &str = "merge";
+pub const CMD_LINT: &str = "lint";
+
+pub const ARG_CO_OCCURRING: &str = "co-occurring";
+pub const ARG_OLD: &str = "old";
+pub const ARG_NEW: &str = "new";
+pub const ARG_TAGS: &str = "tags";
+pub const ARG_INTO: &str = "into";
+pub const ARG_DRY_RUN: &str = "dry-run";
+pub const ARG_FIX: &str = "fix";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .subcommands([
+            Command::new(CMD_LIST)
+                .args([Arg::new(ARG_CO_OCCURRING)
+                    .short('c')
+                    .long(ARG_CO_OCCURRING)
+                    .value_name("count")
+                    .num_args(0..=1)
+                    .default_missing_value("3")
+                    .value_parser(value_parser!(usize))
+                    .help("display the tags used most often together with each tag")])
+                .about("list the tags with the number of notes and the last use"),
+            Command::new(CMD_TREE).about("show the hierarchy of the tags with the number of notes"),
+            Command::new(CMD_RENAME)
+                .args([
+                    Arg::new(ARG_OLD).required(true).help("the tag to rename"),
+                    Arg::new(ARG_NEW)
+                        .required(true)
+                        .help("the new name of the tag"),
+                    dry_run_arg(),
+                ])
+                .about("rename a tag in all notes"),
+            Command::new(CMD_MERGE)
+                .args([
+                    Arg::new(ARG_TAGS)
+                        .required(true)
+                        .num_args(1..)
+                        .help("the tags to merge"),
+                    Arg::new(ARG_INTO)
+                        .long(ARG_INTO)
+                        .required(true)
+                        .help("the tag to merge into"),
+                    dry_run_arg(),
+                ])
+                .about("merge tags into one tag in all notes"),
+            Command::new(CMD_LINT)
+                .args([
+                    Arg::new(ARG_FIX)
+                        .long(ARG_FIX)
+                        .action(ArgAction::SetTrue)
+                        .help("rename the tags of each group to the most used tag"),
+                    Arg::new(ARG_INTO)
+                        .long(ARG_INTO)
+                        .action(ArgAction::Append)
+                        .value_name("tag")
+                        .help("rename the group of this tag to it instead of the most used tag"),
+                    dry_run_arg(),
+                ])
+                .about("find tags differing only in casing, plural or typos"),
+        ])
+        .subcommand_required(true)
+        .about("inspect and edit the tags in your notes")
+}
+
+pub fn run(command: &ArgMatches, term: &mut Term, config: &Config) {
+    match command.subcommand().unwrap() {
+        (CMD_LIST, command) => run_list(command, term, config),
+        (CMD_TREE, _) => run_tree(term, config),
+        (CMD_RENAME, command) => {
+            let old = command.get_one::<String>(ARG_OLD).unwrap().clone();
+            let new = command.get_one::<String>(ARG_NEW).unwrap().clone();
+            let rename = TagRename::new([old], new);
+            run_rename(&rename, command.get_flag(ARG_DRY_RUN), term, config);
+        }
+        (CMD_MERGE, command) => {
+            let tags = command.get_many::<String>(ARG_TAGS).unwrap().cloned();
+            let into = command.get_one::<String>(ARG_INTO).unwrap().clone();
+            let rename = TagRename::new(tags, into);
+            run_rename(&rename, command.get_flag(ARG_DRY_RUN), term, config);
+        }
+        (CMD_LINT, command) => run_lint(command, term, config),
+        (name, _) => term.error(format_args!(
+            "command '{} {}' is not implemented",
+            NAME, name
+        )),
+    }
+}
+
+pub fn dry_run_arg() -> Arg {
+    Arg::new(ARG_DRY_RUN)
+        .long(ARG_DRY_RUN)
+        .action(ArgAction::SetTrue)
+        .help("only show the changes without editing the notes")
+}
+
+fn run_list(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let Ok(notes) = Note::all_notes(config) else {
+        term.error("could not read notes directory!");
+        return;
+    };
+    let notes: Vec<Note> = notes.collect();
+    let co_occurring = command
+        .get_one::<usize>(ARG_CO_OCCURRING)
+        .copied()
+        .unwrap_or_default();
+    let stats = TagStats::collect(&notes, config, co_occurring);
+    term.headline("TAGS");
+    if stats.is_empty() {
+        term.info("no tags found");
+    }
+    for stats in &stats {
+        term.tag_stats(stats);
+    }
+}
+
+fn run_tree(term: &mut Term, config: &Config) {
+    let Ok(notes) = Note::all_notes(config) else {
+        term.error("could not read notes directory!");
+        return;
+    };
+    let notes: Vec<Note> = notes.collect();
+    let tree = TagTree::build(&notes);
+    term.headline("TAG TREE");
+    if tree.children().next().is_none() {
+        term.info("no tags found");
+    } else {
+        term.tag_tree(&tree);
+    }
+}
+
+fn run_lint(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let Ok(notes) = Note::all_notes(config) else {
+        term.error("could not read notes directory!");
+        return;
+    };
+    let notes: Vec<Note> = notes.collect();
+    let mut groups = TagGroup::lint(&notes);
+    for into in command.get_many::<String>(ARG_INTO).into_iter().flatten() {
+        if !groups.iter_mut().any(|group| group.set_canonical(into)) {
+            term.error(format_args!("no suspicious tag named '{}'", into));
+            return;
+        }
+    }
+    term.headline("SUSPICIOUS TAGS");
+    if groups.is_empty() {
+        term.info("no suspicious tags found");
+        return;
+    }
+    for group in &groups {
+        term.tag_group(group);
+    }
+    let dry_run = command.get_flag(ARG_DRY_RUN);
+    if !command.get_flag(ARG_FIX) && !dry_run {
+        term.hint("run 'scatternotes tags lint --fix' to rename the tags to the most used tag");
+        return;
+    }
+    let mut groups = groups.iter().map(TagGroup::rename);
+    let mut rename = groups.next().unwrap();
+    for other in groups {
+        rename.extend(other);
+    }
+    run_rename(&rename, dry_run, term, config);
+}
+
+/// Rename the tags in all notes and print the changes.
+pub fn run_rename(rename: &TagRename, dry_run: bool, term: &mut Term, config: &Config) {
+    let Ok(notes) = Note::all_notes(config) else {
+        term.error("could not read notes directory!");
+        return;
+    };
+    term.headline(if dry_run { "CHANGES" } else { "RENAMED TAGS" });
+    let mut changed = 0;
+    for note in notes {
+        let Some(content) = rename.apply(note.content()) else {
+            continue;
+        };
+        if !dry_run {
+            let result = Note::edit(note.path().to_owned(), |content| {
+                match rename.apply(content) {
+                    Some(renamed) => {
+                        *content = renamed;
+                        true
+                    }
+                    None => false,
+                }
+            });
+            if let Err(error) = result {
+                term.file_error(note.path(), error);
+                continue;
+            }
+        }
+        term.diff(note.path(), &LineChange::diff(note.content(), &content));
+        changed += 1;
+    }
+    match (changed, dry_run) {
+        (0, _) => term.info("no tags to rename found"),
+        (_, true) => term.info(format_args!("{} notes would be changed", changed)),
+        (_, false) => term.info(format_args!("{} notes changed", changed)),
+    }
+}
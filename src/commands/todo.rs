@@ -1,10 +1,16 @@
 use clap::{Arg, ArgAction, ArgMatches, Command};
+use itertools::Itertools;
 
 use crate::config::Config;
 use crate::note::Note;
 use crate::output::{OutputFmt, Term};
+use crate::query::Query;
+use crate::saved::SavedSearches;
 use crate::todo::Todo;
 
+use super::sort::{self, Sorting};
+use super::{filter, search};
+
 pub const NAME: &'static str = "todo";
 
 pub const CMD_LIST: &'static str = "list";
@@ -12,67 +18,116 @@ pub const CMD_SEARCH: &'static str = "search";
 
 pub const ARG_QUERIES: &'static str = "queries";
 pub const ARG_DONE: &'static str = "done";
+pub const ARG_SAVED: &str = "saved";
 
 pub fn command() -> Command {
-    let arg_done = Arg::new("done")
-        .long("done")
-        .action(ArgAction::SetTrue)
-        .help("also list done tags");
     Command::new(NAME)
         .subcommand_required(true)
         .subcommands([
             Command::new(CMD_LIST)
-                .arg(arg_done.clone())
+                .arg(done_arg())
+                .args(filter::args())
+                .args(sort::args())
                 .about("list all todos"),
-            Command::new(CMD_SEARCH)
-                .args([
-                    Arg::new(ARG_QUERIES)
-                        .num_args(1..)
-                        .help("the tags to search for. (the tags are additive)"),
-                    arg_done,
-                ])
-                .about("search through your todos"),
+            search_command().arg(
+                Arg::new(ARG_SAVED)
+                    .long(ARG_SAVED)
+                    .help("use the queries and options of a saved search"),
+            ),
         ])
         .about("find you todos")
 }
 
-pub fn run(command: &ArgMatches, term: &mut Term, config: &Config) {
-    match command.subcommand().unwrap() {
-        (CMD_LIST, command) => {
-            let view_done = command.get_flag(ARG_DONE);
-            run_list(term, config, view_done);
-        }
-        (CMD_SEARCH, command) => {
-            let queries: Vec<_> = command
-                .get_many::<String>(ARG_QUERIES)
-                .unwrap_or_default()
-                .into_iter()
-                .collect();
-            let view_done = command.get_flag(ARG_DONE);
-            run_search(term, config, queries.as_slice(), view_done)
-        }
-        (_, _) => term.error("command not implemented yet"),
-    }
+pub fn done_arg() -> Arg {
+    Arg::new(ARG_DONE)
+        .long(ARG_DONE)
+        .action(ArgAction::SetTrue)
+        .help("also list done tags")
 }
 
-fn run_list(term: &mut Term, config: &Config, view_done: bool) {
-    for note in Note::all_notes(config).unwrap() {
-        for todo in Todo::all(&note) {
-            if !view_done && todo.is_done() {
-                continue;
-            }
-            term.todo(note.path(), todo.content());
-        }
+fn search_command() -> Command {
+    Command::new(CMD_SEARCH)
+        .args([
+            Arg::new(ARG_QUERIES)
+                .num_args(1..)
+                .help("the query to search for. (supports AND, OR, NOT, parentheses and quotes)"),
+            done_arg(),
+            search::match_arg(),
+        ])
+        .args(filter::args())
+        .args(sort::args())
+        .args_override_self(true)
+        .about("search through your todos")
+}
+
+pub fn run(command: &ArgMatches, term: &mut Term, config: &Config) {
+    let (name, command) = command.subcommand().unwrap();
+    let Some(saved) = (name == CMD_SEARCH)
+        .then(|| command.get_one::<String>(ARG_SAVED))
+        .flatten()
+    else {
+        return run_todos(name, command, term, config);
+    };
+
+    let Some(mut search) = SavedSearches::load(config)
+        .ok()
+        .and_then(|searches| searches.get(saved).cloned())
+    else {
+        term.error(format_args!("no saved search named '{}'", saved));
+        term.info("run 'scatternotes search saved' to list the saved searches");
+        return;
+    };
+    search.extend(search::command_line_args(
+        &search_command(),
+        command,
+        &[ARG_SAVED],
+    ));
+    match search::parse_saved(search_command(), &search) {
+        Ok(matches) => run_todos(CMD_SEARCH, &matches, term, config),
+        Err(error) => term.error(error.render()),
     }
 }
 
-fn run_search(term: &mut Term, config: &Config, queries: &[&String], view_done: bool) {
-    for note in Note::search(config, queries).unwrap() {
-        for todo in Todo::all(&note) {
-            if !view_done && todo.is_done() {
-                continue;
+fn run_todos(name: &str, command: &ArgMatches, term: &mut Term, config: &Config) {
+    let filter = match filter::date_filter(command) {
+        Ok(filter) => filter,
+        Err(error) => return term.error(error),
+    };
+    let query = match name {
+        CMD_LIST => None,
+        CMD_SEARCH => {
+            let queries = command
+                .get_many::<String>(ARG_QUERIES)
+                .unwrap_or_default()
+                .join(" ");
+            match Query::parse(&queries) {
+                Ok(query) => Some((query, search::match_mode(command, config))),
+                Err(error) => return term.error(error),
             }
-            term.todo(note.path(), todo.content());
         }
+        _ => return term.error("command not implemented yet"),
+    };
+    let notes: Vec<Note> = match &query {
+        Some((query, mode)) => Note::search(config, query, *mode).unwrap().collect(),
+        None => Note::all_notes(config).unwrap().collect(),
+    };
+    let show_dates = filter::show_dates(command, &filter);
+    let sorting = Sorting::from_matches(command);
+    let view_done = command.get_flag(ARG_DONE);
+
+    let mut notes: Vec<Note> = notes
+        .into_iter()
+        .filter(|note| filter.matches(note.date(config)))
+        .collect();
+    let query = query.as_ref().map(|(query, mode)| (query, *mode));
+    sorting.sort(&mut notes, config, query, |note| note);
+    let todos = notes.iter().flat_map(|note| {
+        Todo::all(note)
+            .filter(|todo| view_done || !todo.is_done())
+            .map(move |todo| (note, todo))
+    });
+    for (note, todo) in todos.take(sorting.limit()) {
+        let date = note.date(config).filter(|_| show_dates);
+        term.todo(note.path(), date, todo.content());
     }
 }
@@ -5,22 +5,70 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use std::collections::BTreeMap;
+
 use chrono::NaiveDate;
+use eyre::eyre;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::query::MatchMode;
+use crate::tag::{ActionTag, TodoTag};
+
+/// Paths missing in the config file default to `~/notes` and its `code`,
+/// `carlender` and `meta` directories.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     path: PathBuf,
     code_path: PathBuf,
     carlender_path: PathBuf,
     meta_path: PathBuf,
+    match_mode: MatchMode,
+    keywords: Keywords,
+}
+
+/// Additional words for the todo and action tags, e.g. in another language.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Keywords {
+    /// keywords mapped to a todo tag like `done`
+    #[serde(default)]
+    pub todo: BTreeMap<String, TodoTag>,
+    /// keywords mapped to an action tag like `split`
+    #[serde(default)]
+    pub action: BTreeMap<String, ActionTag>,
+    /// custom todo states, which are either open or closed
+    #[serde(default)]
+    pub states: BTreeMap<String, TodoState>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoState {
+    Open,
+    Closed,
 }
 
 impl Config {
-    pub fn load() -> Config {
-        read_to_string("~/.scatternotes.json")
-            .map(|content| serde_json::from_str(&content).expect("Could not parse you config."))
-            .unwrap_or_default()
+    pub fn load() -> eyre::Result<Config> {
+        let home = env::var("HOME").map_err(|_| eyre!("could not find the home directory"))?;
+        let mut config: Config = match read_to_string(home.clone().add("/.scatternotes.json")) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|error| eyre!("could not parse '~/.scatternotes.json': {}", error))?,
+            Err(_) => Config::default(),
+        };
+        if config.path.as_os_str().is_empty() {
+            config.path = PathBuf::from(home).join("notes");
+        }
+        for (path, name) in [
+            (&mut config.code_path, "code"),
+            (&mut config.carlender_path, "carlender"),
+            (&mut config.meta_path, "meta"),
+        ] {
+            if path.as_os_str().is_empty() {
+                *path = config.path.join(name);
+            }
+        }
+        Ok(config)
     }
 
     pub fn new(
@@ -34,6 +82,8 @@ impl Config {
             code_path,
             carlender_path,
             meta_path,
+            match_mode: MatchMode::default(),
+            keywords: Keywords::default(),
         })
     }
 
@@ -57,6 +107,21 @@ impl Config {
         self.meta_path.join(name)
     }
 
+    /// Check if the path is the code, carlender or meta directory.
+    pub fn is_special_path(&self, path: &Path) -> bool {
+        [&self.code_path, &self.carlender_path, &self.meta_path]
+            .iter()
+            .any(|special| special.as_path() == path)
+    }
+
+    pub fn match_mode(&self) -> MatchMode {
+        self.match_mode
+    }
+
+    pub fn keywords(&self) -> &Keywords {
+        &self.keywords
+    }
+
     pub fn carlender(&self, date: NaiveDate) -> PathBuf {
         ensure_directory_exists(&self.carlender_path).unwrap();
         self.carlender_path
@@ -64,16 +129,6 @@ impl Config {
     }
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        let path: PathBuf = env::var("HOME").unwrap().add("/notes").into();
-        let code_path = path.join("code");
-        let carlender_path = path.join("carlender");
-        let meta_path = path.join("meta");
-        Self::new(path, code_path, carlender_path, meta_path).unwrap()
-    }
-}
-
 fn ensure_directory_exists(path: impl AsRef<Path>) -> eyre::Result<()> {
     let path = path.as_ref();
     if !path.exists() || !path.is_dir() {
@@ -0,0 +1,211 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use eyre::eyre;
+use nom::character::complete::{one_of, u32};
+use nom::combinator::all_consuming;
+use nom::sequence::pair;
+use nom::IResult;
+
+/// An inclusive range of days.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DateRange {
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+/// Filters notes by their date. Notes without a date never match an active
+/// filter.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct DateFilter {
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn day(date: NaiveDate) -> Self {
+        Self {
+            start: date,
+            end: date,
+        }
+    }
+
+    /// Parse a date expression relative to today.
+    ///
+    /// Supported are iso dates (`2024-05-01`), months (`2024-05`), `today`,
+    /// `yesterday`, a number of days, weeks, months or years ago (`7d`, `2w`,
+    /// `3m`, `1y`) and the current or last week, month or year (`this-week`,
+    /// `last-month`, ...).
+    pub fn parse(input: &str, today: NaiveDate) -> eyre::Result<Self> {
+        let input = input.trim();
+        if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+            return Ok(Self::day(date));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(&format!("{}-01", input), "%Y-%m-%d") {
+            return Ok(Self::month(date));
+        }
+        let range = match input {
+            "today" => Some(Self::day(today)),
+            "yesterday" => today.checked_sub_days(Days::new(1)).map(Self::day),
+            "this-week" => Some(Self::week(today)),
+            "last-week" => today.checked_sub_days(Days::new(7)).map(Self::week),
+            "this-month" => Some(Self::month(today)),
+            "last-month" => today.checked_sub_months(Months::new(1)).map(Self::month),
+            "this-year" => Some(Self::year(today)),
+            "last-year" => today.checked_sub_months(Months::new(12)).map(Self::year),
+            _ => parse_ago(input, today).map(Self::day),
+        };
+        range.ok_or_else(|| eyre!("invalid date: {}", input))
+    }
+
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
+
+    pub fn end(&self) -> NaiveDate {
+        self.end
+    }
+}
+
+impl DateRange {
+    fn week(date: NaiveDate) -> Self {
+        let week = date.week(Weekday::Mon);
+        Self {
+            start: week.first_day(),
+            end: week.last_day(),
+        }
+    }
+
+    fn month(date: NaiveDate) -> Self {
+        let start = date.with_day(1).unwrap();
+        let end = start
+            .checked_add_months(Months::new(1))
+            .and_then(|next| next.pred_opt())
+            .unwrap_or(NaiveDate::MAX);
+        Self { start, end }
+    }
+
+    fn year(date: NaiveDate) -> Self {
+        let start = date.with_ordinal(1).unwrap();
+        let end = NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap();
+        Self { start, end }
+    }
+}
+
+impl DateFilter {
+    pub fn since(&mut self, range: DateRange) {
+        self.since = self.since.max(Some(range.start));
+    }
+
+    pub fn until(&mut self, range: DateRange) {
+        self.until = match self.until {
+            Some(until) => Some(until.min(range.end)),
+            None => Some(range.end),
+        };
+    }
+
+    pub fn on(&mut self, range: DateRange) {
+        self.since(range);
+        self.until(range);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    pub fn matches(&self, date: Option<NaiveDate>) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        let Some(date) = date else {
+            return false;
+        };
+        self.since.is_none_or(|since| since <= date) && self.until.is_none_or(|until| date <= until)
+    }
+}
+
+fn parse_ago(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    fn ago_impl(input: &str) -> IResult<&str, (u32, char)> {
+        all_consuming(pair(u32, one_of("dwmy")))(input)
+    }
+
+    let (_, (count, unit)) = ago_impl(input).ok()?;
+    match unit {
+        'd' => today.checked_sub_days(Days::new(count as u64)),
+        'w' => today.checked_sub_days(Days::new(count as u64 * 7)),
+        'm' => today.checked_sub_months(Months::new(count)),
+        'y' => today.checked_sub_months(Months::new(count.checked_mul(12)?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{DateFilter, DateRange};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// A wednesday.
+    fn today() -> NaiveDate {
+        date(2024, 3, 13)
+    }
+
+    fn range(input: &str) -> (NaiveDate, NaiveDate) {
+        let range = DateRange::parse(input, today()).unwrap();
+        (range.start(), range.end())
+    }
+
+    #[test]
+    fn parses_absolute_dates() {
+        assert_eq!(range("2024-01-05"), (date(2024, 1, 5), date(2024, 1, 5)));
+        assert_eq!(range(" 2024-02 "), (date(2024, 2, 1), date(2024, 2, 29)));
+        assert_eq!(range("2023-12"), (date(2023, 12, 1), date(2023, 12, 31)));
+    }
+
+    #[test]
+    fn parses_named_ranges() {
+        assert_eq!(range("today"), (today(), today()));
+        assert_eq!(range("yesterday"), (date(2024, 3, 12), date(2024, 3, 12)));
+        assert_eq!(range("this-week"), (date(2024, 3, 11), date(2024, 3, 17)));
+        assert_eq!(range("last-week"), (date(2024, 3, 4), date(2024, 3, 10)));
+        assert_eq!(range("this-month"), (date(2024, 3, 1), date(2024, 3, 31)));
+        assert_eq!(range("last-month"), (date(2024, 2, 1), date(2024, 2, 29)));
+        assert_eq!(range("this-year"), (date(2024, 1, 1), date(2024, 12, 31)));
+        assert_eq!(range("last-year"), (date(2023, 1, 1), date(2023, 12, 31)));
+    }
+
+    #[test]
+    fn parses_relative_days() {
+        assert_eq!(range("7d").0, date(2024, 3, 6));
+        assert_eq!(range("2w").0, date(2024, 2, 28));
+        assert_eq!(range("1m").0, date(2024, 2, 13));
+        assert_eq!(range("1y").0, date(2023, 3, 13));
+        assert_eq!(range("0d").0, today());
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for input in ["", "last week", "2024-13", "2024-02-30", "7x", "-1d", "d"] {
+            assert!(DateRange::parse(input, today()).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn filters_by_the_combined_range() {
+        let mut filter = DateFilter::default();
+        assert!(filter.matches(None));
+        filter.since(DateRange::parse("2024-01", today()).unwrap());
+        filter.until(DateRange::parse("2024-02", today()).unwrap());
+        assert!(filter.matches(Some(date(2024, 1, 1))));
+        assert!(filter.matches(Some(date(2024, 2, 29))));
+        assert!(!filter.matches(Some(date(2024, 3, 1))));
+        assert!(!filter.matches(None));
+
+        let mut filter = DateFilter::default();
+        filter.on(DateRange::parse("last-month", today()).unwrap());
+        assert!(filter.matches(Some(date(2024, 2, 10))));
+        assert!(!filter.matches(Some(date(2024, 1, 31))));
+    }
+}
@@ -1,2 +1,190 @@
+use std::collections::HashMap;
 
+use serde::Serialize;
 
+use crate::tag::{iter_non_code_blocks, Tag, HIERARCHY_SEPARATOR};
+
+/// Rewrites name tags to another name. Nested tags are moved along with
+/// their parent, so renaming `project` also renames `#project/web`. Persons
+/// are only renamed if they are written exactly like given.
+#[derive(Debug, Default, Clone)]
+pub struct TagRename {
+    renames: Vec<(String, String)>,
+    persons: HashMap<String, String>,
+}
+
+/// A line of a note before and after an edit.
+#[derive(Debug, Clone, Serialize)]
+pub struct LineChange {
+    line: usize,
+    before: String,
+    after: String,
+}
+
+impl TagRename {
+    pub fn new(from: impl IntoIterator<Item = String>, to: String) -> Self {
+        let to = to.trim_start_matches('#');
+        let renames = from
+            .into_iter()
+            .map(|tag| tag.trim_start_matches('#').to_owned())
+            .filter(|tag| !tag.is_empty())
+            .map(|tag| (tag, to.to_owned()))
+            .collect();
+        Self {
+            renames,
+            persons: HashMap::new(),
+        }
+    }
+
+    /// Rename the mentions of persons to the given handles.
+    pub fn persons(renames: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self {
+            renames: Vec::new(),
+            persons: renames.into_iter().collect(),
+        }
+    }
+
+    /// Apply the renames of the other rename as well.
+    pub fn extend(&mut self, other: TagRename) {
+        self.renames.extend(other.renames);
+        self.persons.extend(other.persons);
+    }
+
+    /// The content with all tags renamed or `None` if nothing changed. Tags
+    /// inside of code blocks are left untouched.
+    pub fn apply(&self, content: &str) -> Option<String> {
+        let mut result = String::with_capacity(content.len());
+        let mut copied = 0;
+        for block in iter_non_code_blocks(content) {
+            let mut input = block;
+            while !input.is_empty() {
+                match Tag::parse_next(input) {
+                    Ok((preceding, remaining, tag)) => {
+                        // skip the `#` or `@`, the text of keyword todos
+                        // differs from how they are written
+                        let start = offset(content, input) + preceding.len() + 1;
+                        let end = offset(content, remaining);
+                        if let Some(renamed) = self.rename(&tag) {
+                            result.push_str(&content[copied..start]);
+                            result.push_str(&renamed);
+                            copied = end;
+                        }
+                        input = remaining;
+                    }
+                    Err((_, remaining)) => input = remaining,
+                }
+            }
+        }
+        if copied == 0 {
+            return None;
+        }
+        result.push_str(&content[copied..]);
+        Some(result)
+    }
+
+    fn rename(&self, tag: &Tag) -> Option<String> {
+        let name: &str = match tag {
+            Tag::Name(name) => name,
+            Tag::Person(person) => {
+                let renamed = self.persons.get(person.name())?;
+                return (renamed != person.name()).then(|| renamed.clone());
+            }
+            _ => return None,
+        };
+        self.renames.iter().find_map(|(from, to)| {
+            let rest = name.strip_prefix(from.as_str())?;
+            if !rest.is_empty() && !rest.starts_with(HIERARCHY_SEPARATOR) {
+                return None;
+            }
+            let renamed = format!("{}{}", to, rest);
+            (renamed != name).then_some(renamed)
+        })
+    }
+}
+
+impl LineChange {
+    /// The changed lines of an edit, which did not add or remove any lines.
+    pub fn diff(before: &str, after: &str) -> Vec<Self> {
+        before
+            .lines()
+            .zip(after.lines())
+            .enumerate()
+            .filter(|(_, (before, after))| before != after)
+            .map(|(index, (before, after))| Self {
+                line: index + 1,
+                before: before.to_owned(),
+                after: after.to_owned(),
+            })
+            .collect()
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn before(&self) -> &str {
+        &self.before
+    }
+
+    pub fn after(&self) -> &str {
+        &self.after
+    }
+}
+
+fn offset(content: &str, part: &str) -> usize {
+    part.as_ptr() as usize - content.as_ptr() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineChange, TagRename};
+
+    fn rename(from: &str, to: &str, content: &str) -> Option<String> {
+        TagRename::new([from.to_owned()], to.to_owned()).apply(content)
+    }
+
+    #[test]
+    fn renames_nested_tags_with_their_parent() {
+        assert_eq!(
+            rename("project", "work", "#project #project/web #projects").as_deref(),
+            Some("#work #work/web #projects")
+        );
+        assert_eq!(
+            rename("#project/web", "#site", "#project #project/web/api").as_deref(),
+            Some("#project #site/api")
+        );
+    }
+
+    #[test]
+    fn keeps_code_blocks() {
+        let content = "#k8s\n```\n#k8s\n```\n`#k8s` and #k8s.";
+        assert_eq!(
+            rename("k8s", "kubernetes", content).as_deref(),
+            Some("#kubernetes\n```\n#k8s\n```\n`#k8s` and #kubernetes.")
+        );
+    }
+
+    #[test]
+    fn keeps_unchanged_content() {
+        assert_eq!(rename("k8s", "kubernetes", "#kube @k8s k8s"), None);
+        assert_eq!(rename("k8s", "k8s", "#k8s"), None);
+    }
+
+    #[test]
+    fn renames_persons_written_exactly_like_given() {
+        let renames = TagRename::persons([("Bob".to_owned(), "bob".to_owned())]);
+        assert_eq!(
+            renames.apply("@Bob and @bob and @BOB").as_deref(),
+            Some("@bob and @bob and @BOB")
+        );
+    }
+
+    #[test]
+    fn diffs_the_changed_lines() {
+        let changes = LineChange::diff("a\n#k8s\nb", "a\n#kubernetes\nb");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].line(), 2);
+        assert_eq!(changes[0].before(), "#k8s");
+        assert_eq!(changes[0].after(), "#kubernetes");
+    }
+}
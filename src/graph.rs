@@ -0,0 +1,154 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use clap::ValueEnum;
+use itertools::Itertools;
+use serde::Serialize;
+
+/// An undirected graph of things mentioned together in the same notes. The
+/// weight of an edge is the number of notes mentioning both nodes.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Graph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    id: String,
+    count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    source: String,
+    target: String,
+    weight: usize,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// graphviz dot
+    #[default]
+    Dot,
+    /// nodes and edges with weights
+    Json,
+}
+
+impl Graph {
+    /// Build the graph from the nodes mentioned in every note.
+    pub fn co_occurrence<I>(notes: impl IntoIterator<Item = I>) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        let mut weights: BTreeMap<(String, String), usize> = BTreeMap::new();
+        for nodes in notes {
+            let nodes: BTreeSet<String> = nodes.into_iter().collect();
+            for node in &nodes {
+                *counts.entry(node.clone()).or_insert(0) += 1;
+            }
+            for (source, target) in nodes.iter().tuple_combinations() {
+                *weights.entry((source.clone(), target.clone())).or_insert(0) += 1;
+            }
+        }
+        Self {
+            nodes: counts
+                .into_iter()
+                .map(|(id, count)| GraphNode { id, count })
+                .collect(),
+            edges: weights
+                .into_iter()
+                .map(|((source, target), weight)| GraphEdge {
+                    source,
+                    target,
+                    weight,
+                })
+                .collect(),
+        }
+    }
+
+    /// Remove the edges with a weight below the threshold.
+    pub fn prune(&mut self, min_weight: usize) {
+        self.edges.retain(|edge| edge.weight >= min_weight);
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.nodes.iter().map(|node| (node.id.as_str(), node.count))
+    }
+
+    /// The nodes connected to the given node, strongest connection first.
+    pub fn neighbours<'a>(&'a self, id: &'a str) -> impl Iterator<Item = (&'a str, usize)> + 'a {
+        self.edges
+            .iter()
+            .filter_map(move |edge| {
+                if edge.source == id {
+                    Some((edge.target.as_str(), edge.weight))
+                } else if edge.target == id {
+                    Some((edge.source.as_str(), edge.weight))
+                } else {
+                    None
+                }
+            })
+            .sorted_by(|(left, left_weight), (right, right_weight)| {
+                right_weight.cmp(left_weight).then(left.cmp(right))
+            })
+    }
+
+    pub fn render(&self, format: GraphFormat) -> eyre::Result<String> {
+        match format {
+            GraphFormat::Dot => Ok(self.to_dot()),
+            GraphFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("graph {\n");
+        for node in &self.nodes {
+            writeln!(dot, "  {} [count={}];", dot_id(&node.id), node.count).unwrap();
+        }
+        for edge in &self.edges {
+            writeln!(
+                dot,
+                "  {} -- {} [weight={}, penwidth={}];",
+                dot_id(&edge.source),
+                dot_id(&edge.target),
+                edge.weight,
+                edge.weight
+            )
+            .unwrap();
+        }
+        dot.push('}');
+        dot
+    }
+}
+
+/// A quoted DOT id. Only quotes and backslashes are escaped, everything else
+/// is taken literally by graphviz.
+fn dot_id(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Graph, GraphFormat};
+
+    #[test]
+    fn quotes_the_ids_in_dot() {
+        let graph =
+            Graph::co_occurrence([vec!["#say\"hi\"".to_owned(), "#cafe\u{301}".to_owned()]]);
+        let dot = graph.render(GraphFormat::Dot).unwrap();
+        assert_eq!(
+            dot,
+            "graph {\n  \"#cafe\u{301}\" [count=1];\n  \"#say\\\"hi\\\"\" [count=1];\n  \
+             \"#cafe\u{301}\" -- \"#say\\\"hi\\\"\" [weight=1, penwidth=1];\n}"
+        );
+    }
+
+    #[test]
+    fn escapes_backslashes_in_dot() {
+        let graph = Graph::co_occurrence([vec!["@a\\b".to_owned()]]);
+        let dot = graph.render(GraphFormat::Dot).unwrap();
+        assert_eq!(dot, "graph {\n  \"@a\\\\b\" [count=1];\n}");
+    }
+}
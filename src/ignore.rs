@@ -0,0 +1,194 @@
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+
+const IGNORE_FILE: &str = ".scatternotesignore";
+
+/// Gitignore-style rules from the `.scatternotesignore` file in the notes
+/// directory.
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug)]
+struct IgnoreRule {
+    pattern: String,
+    negated: bool,
+    directory_only: bool,
+    anchored: bool,
+}
+
+impl IgnoreRules {
+    pub fn load(config: &Config) -> Self {
+        fs::read_to_string(config.note(IGNORE_FILE))
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(IgnoreRule::parse)
+            .collect();
+        Self { rules }
+    }
+
+    /// Check if the path relative to the notes directory is ignored. The last
+    /// matching rule decides.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let path = path.to_string_lossy().replace('\\', "/");
+        let name = path.rsplit('/').next().unwrap_or(&path);
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.directory_only && !is_dir {
+                continue;
+            }
+            let text = if rule.anchored { path.as_str() } else { name };
+            if glob_match(&rule.pattern, text) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Self {
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (directory_only, line) = match line.strip_suffix('/') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let pattern = line.trim_start_matches('/').to_owned();
+        Self {
+            pattern,
+            negated,
+            directory_only,
+            anchored,
+        }
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    if let Some(pattern) = pattern.strip_prefix("**") {
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        return text
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain([text.len()])
+            .any(|index| glob_match(pattern, &text[index..]));
+    }
+    let mut pattern_chars = pattern.chars();
+    let Some(first) = pattern_chars.next() else {
+        return text.is_empty();
+    };
+    let rest = pattern_chars.as_str();
+    match first {
+        '*' => text
+            .char_indices()
+            .take_while(|(_, char)| *char != '/')
+            .map(|(index, _)| index)
+            .chain([text.find('/').unwrap_or(text.len())])
+            .any(|index| glob_match(rest, &text[index..])),
+        '?' => match text.chars().next() {
+            Some(char) if char != '/' => glob_match(rest, &text[char.len_utf8()..]),
+            _ => false,
+        },
+        '[' => {
+            let Some(char) = text.chars().next() else {
+                return false;
+            };
+            let Some((class, rest)) = rest.split_once(']') else {
+                return text.starts_with('[') && glob_match(rest, &text[1..]);
+            };
+            class_match(class, char) && glob_match(rest, &text[char.len_utf8()..])
+        }
+        char => text
+            .strip_prefix(char)
+            .is_some_and(|text| glob_match(rest, text)),
+    }
+}
+
+fn class_match(class: &str, char: char) -> bool {
+    let (negated, class) = match class.strip_prefix('!') {
+        Some(class) => (true, class),
+        None => (false, class),
+    };
+    let chars: Vec<char> = class.chars().collect();
+    let mut matched = false;
+    let mut index = 0;
+    while index < chars.len() {
+        if index + 2 < chars.len() && chars[index + 1] == '-' {
+            matched |= (chars[index]..=chars[index + 2]).contains(&char);
+            index += 3;
+        } else {
+            matched |= chars[index] == char;
+            index += 1;
+        }
+    }
+    matched != negated
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::IgnoreRules;
+
+    fn is_ignored(rules: &str, path: &str, is_dir: bool) -> bool {
+        IgnoreRules::parse(rules).is_ignored(Path::new(path), is_dir)
+    }
+
+    #[test]
+    fn directory_patterns_only_match_directories() {
+        assert!(is_ignored("drafts/", "drafts", true));
+        assert!(is_ignored("drafts/", "team/drafts", true));
+        assert!(!is_ignored("drafts/", "drafts", false));
+    }
+
+    #[test]
+    fn unanchored_patterns_match_the_name() {
+        assert!(is_ignored("*.tmp", "a.tmp", false));
+        assert!(is_ignored("*.tmp", "team/sub/a.tmp", false));
+        assert!(is_ignored("note-?.md", "note-1.md", false));
+        assert!(is_ignored("note-[0-9].md", "note-7.md", false));
+        assert!(!is_ignored("note-[!0-9].md", "note-7.md", false));
+        assert!(!is_ignored("*.tmp", "a.md", false));
+    }
+
+    #[test]
+    fn anchored_patterns_match_the_whole_path() {
+        assert!(is_ignored("/drafts", "drafts", true));
+        assert!(!is_ignored("/drafts", "team/drafts", true));
+        assert!(is_ignored("team/*.md", "team/a.md", false));
+        assert!(!is_ignored("team/*.md", "team/sub/a.md", false));
+        assert!(!is_ignored("team/*.md", "other/team/a.md", false));
+    }
+
+    #[test]
+    fn double_stars_match_any_number_of_directories() {
+        let rules = "archive/**/*.md";
+        assert!(is_ignored(rules, "archive/a.md", false));
+        assert!(is_ignored(rules, "archive/2024/01/a.md", false));
+        assert!(!is_ignored(rules, "notes/archive/a.md", false));
+        assert!(is_ignored("**/old", "team/sub/old", true));
+        assert!(is_ignored("**/old", "old", true));
+    }
+
+    #[test]
+    fn the_last_matching_rule_decides() {
+        let rules = "# comment\narchive/**/*.md\n!archive/**/important.md\n";
+        assert!(is_ignored(rules, "archive/2024/a.md", false));
+        assert!(!is_ignored(rules, "archive/2024/important.md", false));
+        assert!(is_ignored("!a.md\n*.md", "a.md", false));
+        assert!(is_ignored("\\!a.md", "!a.md", false));
+    }
+}
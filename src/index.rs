@@ -0,0 +1,222 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::note::Note;
+use crate::tag::Tag;
+use crate::vocabulary::Vocabulary;
+
+const INDEX_VERSION: u32 = 7;
+
+/// Cache of the parsed notes, stored in the meta directory.
+///
+/// Entries are keyed by the path of the note and are only valid as long as
+/// the modification time and the size of the file did not change.
+#[derive(Serialize, Deserialize)]
+pub struct Index {
+    version: u32,
+    vocabulary: u64,
+    entries: HashMap<PathBuf, IndexEntry>,
+    /// set for indexes not read from the meta directory
+    #[serde(skip)]
+    unsaved: bool,
+    /// notes which could not be read during the last update
+    #[serde(skip)]
+    unreadable: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexEntry {
+    modified: SystemTime,
+    size: u64,
+    tags: HashSet<Tag<'static>>,
+    todos: Vec<String>,
+    date: Option<NaiveDate>,
+}
+
+impl Index {
+    pub fn new() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            vocabulary: Vocabulary::get().fingerprint(),
+            entries: HashMap::new(),
+            unsaved: true,
+            unreadable: Vec::new(),
+        }
+    }
+
+    /// Load the index or create an empty one if it is missing or outdated.
+    /// The index is outdated if the vocabulary used for parsing changed.
+    pub fn load(config: &Config) -> Self {
+        fs::read_to_string(index_meta_file(config))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|index| index.version == INDEX_VERSION)
+            .filter(|index| index.vocabulary == Vocabulary::get().fingerprint())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, config: &Config) -> eyre::Result<()> {
+        let content = serde_json::to_string(self)?;
+        fs::write(index_meta_file(config), content)?;
+        Ok(())
+    }
+
+    /// Reparse all notes that changed since the last update, drop the notes
+    /// that were removed and save the index if anything changed. Notes which
+    /// can not be read are left out and listed by [`Index::unreadable`].
+    pub fn update(&mut self, config: &Config) -> eyre::Result<Vec<Note>> {
+        let mut changed = self.unsaved;
+        self.unreadable.clear();
+        let mut notes = Vec::new();
+        let mut seen = HashSet::new();
+        for path in Note::all_paths(config)? {
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+            seen.insert(path.clone());
+            let size = metadata.len();
+            if let Some(entry) = self.entries.get(&path) {
+                if entry.modified == modified && entry.size == size {
+                    // the content of cached notes is read later on
+                    if File::open(&path).is_err() {
+                        self.unreadable.push(path);
+                        continue;
+                    }
+                    notes.push(Note::cached(
+                        path,
+                        entry.tags.clone(),
+                        entry.todos.clone(),
+                        entry.date,
+                    ));
+                    continue;
+                }
+            }
+            let Ok(note) = Note::load(path.clone()) else {
+                self.unreadable.push(path);
+                continue;
+            };
+            let entry = IndexEntry {
+                modified,
+                size,
+                tags: note.tags().clone(),
+                todos: note.todo_parts().to_vec(),
+                date: note.date(config),
+            };
+            self.entries.insert(path, entry);
+            notes.push(note);
+            changed = true;
+        }
+
+        let count = self.entries.len();
+        self.entries.retain(|path, _| seen.contains(path));
+        changed |= count != self.entries.len();
+
+        if changed {
+            self.save(config)?;
+            self.unsaved = false;
+        }
+        Ok(notes)
+    }
+
+    pub fn unreadable(&self) -> &[PathBuf] {
+        &self.unreadable
+    }
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn index_meta_file(config: &Config) -> PathBuf {
+    config.meta("index.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::{index_meta_file, Index, INDEX_VERSION};
+    use crate::config::Config;
+
+    /// A config with an empty notes directory in the temp directory.
+    fn config(name: &str) -> Config {
+        let path = std::env::temp_dir().join(format!("scatternotes-index-{}", name));
+        fs::remove_dir_all(&path).ok();
+        fs::create_dir_all(&path).unwrap();
+        Config::new(
+            path.clone(),
+            path.join("code"),
+            path.join("carlender"),
+            path.join("meta"),
+        )
+        .unwrap()
+    }
+
+    fn write_note(config: &Config, name: &str, content: &str) -> PathBuf {
+        let path = config.note(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn removes_deleted_notes() {
+        let config = config("removes-deleted-notes");
+        let kept = write_note(&config, "2024-01-01_aaaaaaaaaaaaaaaaaaaa.md", "#kept");
+        let removed = write_note(&config, "2024-01-02_bbbbbbbbbbbbbbbbbbbb.md", "#removed");
+        Index::load(&config).update(&config).unwrap();
+        assert_eq!(Index::load(&config).entries.len(), 2);
+
+        fs::remove_file(&removed).unwrap();
+        let notes = Index::load(&config).update(&config).unwrap();
+        assert_eq!(notes.len(), 1);
+        let index = Index::load(&config);
+        assert!(index.entries.contains_key(&kept));
+        assert!(!index.entries.contains_key(&removed));
+    }
+
+    #[test]
+    fn reparses_changed_notes() {
+        let config = config("reparses-changed-notes");
+        let path = write_note(&config, "2024-01-01_aaaaaaaaaaaaaaaaaaaa.md", "#old");
+        Index::load(&config).update(&config).unwrap();
+
+        write_note(&config, "2024-01-01_aaaaaaaaaaaaaaaaaaaa.md", "#new #tags");
+        let notes = Index::load(&config).update(&config).unwrap();
+        assert!(notes[0].has_tag("new"));
+        assert_eq!(Index::load(&config).entries[&path].tags.len(), 2);
+    }
+
+    #[test]
+    fn discards_outdated_indexes() {
+        let config = config("discards-outdated-indexes");
+        write_note(&config, "2024-01-01_aaaaaaaaaaaaaaaaaaaa.md", "#tag");
+        Index::load(&config).update(&config).unwrap();
+        assert_eq!(Index::load(&config).entries.len(), 1);
+
+        let mut index = Index::load(&config);
+        index.version = INDEX_VERSION - 1;
+        index.save(&config).unwrap();
+        assert!(Index::load(&config).entries.is_empty());
+
+        let mut index = Index::load(&config);
+        index.update(&config).unwrap();
+        index.vocabulary = index.vocabulary.wrapping_add(1);
+        index.save(&config).unwrap();
+        assert!(Index::load(&config).entries.is_empty());
+
+        fs::write(index_meta_file(&config), "not json").unwrap();
+        assert!(Index::load(&config).entries.is_empty());
+    }
+}
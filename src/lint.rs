@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+use serde::Serialize;
+use unicase::UniCase;
+
+use crate::edit::TagRename;
+use crate::note::Note;
+use crate::query::{edit_distance, fuzzy_tolerance};
+use crate::tag::Tag;
+
+/// Name tags, which probably mean the same thing.
+#[derive(Debug, Clone, Serialize)]
+pub struct TagGroup {
+    /// The tags with their number of notes, most used first. On a tie the
+    /// lowercase spelling comes first.
+    tags: Vec<(String, usize)>,
+    reasons: Vec<LintReason>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintReason {
+    /// the tags only differ in casing
+    Casing,
+    /// the tags are singular and plural of the same word
+    Plural,
+    /// the tags differ by a typo
+    Typo,
+}
+
+impl TagGroup {
+    /// Cluster the name tags of the notes into groups of suspicious tags.
+    pub fn lint<'a>(notes: impl IntoIterator<Item = &'a Note>) -> Vec<Self> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for note in notes {
+            for tag in note.tags() {
+                if let Tag::Name(name) = tag {
+                    *counts.entry(name).or_insert(0) += 1;
+                }
+            }
+        }
+        let tags: Vec<(&str, usize)> = counts.into_iter().collect();
+
+        // groups are only merged if all of their tags are similar to each
+        // other, so `test`, `tent` and `tint` do not end up in one group
+        let mut groups: Vec<Vec<usize>> = (0..tags.len()).map(|index| vec![index]).collect();
+        let mut membership: Vec<usize> = (0..tags.len()).collect();
+        let mut reasons: BTreeMap<usize, Vec<LintReason>> = BTreeMap::new();
+        for (left, right) in (0..tags.len()).tuple_combinations() {
+            let Some(reason) = LintReason::between(tags[left].0, tags[right].0) else {
+                continue;
+            };
+            let left_group = membership[left];
+            let right_group = membership[right];
+            if left_group != right_group {
+                let similar = groups[left_group]
+                    .iter()
+                    .cartesian_product(&groups[right_group])
+                    .all(|(left, right)| {
+                        LintReason::between(tags[*left].0, tags[*right].0).is_some()
+                    });
+                if !similar {
+                    continue;
+                }
+                let moved = std::mem::take(&mut groups[right_group]);
+                for index in &moved {
+                    membership[*index] = left_group;
+                }
+                groups[left_group].extend(moved);
+                let merged = reasons.remove(&right_group).unwrap_or_default();
+                reasons.entry(left_group).or_default().extend(merged);
+            }
+            reasons.entry(left_group).or_default().push(reason);
+        }
+
+        groups
+            .into_iter()
+            .enumerate()
+            .filter(|(_, members)| members.len() > 1)
+            .map(|(group, members)| Self {
+                tags: members
+                    .into_iter()
+                    .map(|index| (tags[index].0.to_owned(), tags[index].1))
+                    .sorted_by(|(left, left_count), (right, right_count)| {
+                        right_count
+                            .cmp(left_count)
+                            .then(has_uppercase(left).cmp(&has_uppercase(right)))
+                            .then(left.cmp(right))
+                    })
+                    .collect(),
+                reasons: reasons
+                    .remove(&group)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .sorted()
+                    .dedup()
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// The most used tag of the group, unless another tag was chosen.
+    pub fn canonical(&self) -> &str {
+        &self.tags[0].0
+    }
+
+    /// Choose the tag the other tags of the group are renamed to. Returns
+    /// `false` if the tag is not part of the group.
+    pub fn set_canonical(&mut self, tag: &str) -> bool {
+        let tag = tag.trim_start_matches('#');
+        let Some(index) = self.tags.iter().position(|(name, _)| name == tag) else {
+            return false;
+        };
+        let canonical = self.tags.remove(index);
+        self.tags.insert(0, canonical);
+        true
+    }
+
+    pub fn tags(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.tags.iter().map(|(tag, count)| (tag.as_str(), *count))
+    }
+
+    pub fn reasons(&self) -> &[LintReason] {
+        &self.reasons
+    }
+
+    /// Rename all other tags of the group to the canonical tag.
+    pub fn rename(&self) -> TagRename {
+        let others = self.tags.iter().skip(1).map(|(tag, _)| tag.clone());
+        TagRename::new(others, self.canonical().to_owned())
+    }
+}
+
+impl LintReason {
+    fn between(left: &str, right: &str) -> Option<Self> {
+        if UniCase::new(left) == UniCase::new(right) {
+            return Some(Self::Casing);
+        }
+        let left = left.to_lowercase();
+        let right = right.to_lowercase();
+        if singular(&left) == singular(&right) {
+            return Some(Self::Plural);
+        }
+        // typos in the first letter are rare, but words like `rust` and
+        // `just` are common
+        let shorter = if left.len() < right.len() {
+            &left
+        } else {
+            &right
+        };
+        if left.chars().next() == right.chars().next()
+            && edit_distance(&left, &right) <= fuzzy_tolerance(shorter)
+        {
+            return Some(Self::Typo);
+        }
+        None
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Casing => "casing",
+            Self::Plural => "plural",
+            Self::Typo => "typo",
+        }
+    }
+}
+
+/// A rough singular of an english word.
+fn singular(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("ies") {
+        return format!("{}y", stem);
+    }
+    for suffix in ["ses", "xes", "ches", "shes"] {
+        if word.ends_with(suffix) {
+            return word[..word.len() - 2].to_owned();
+        }
+    }
+    match word.strip_suffix('s') {
+        Some(stem) if !stem.ends_with('s') => stem.to_owned(),
+        _ => word.to_owned(),
+    }
+}
+
+fn has_uppercase(tag: &str) -> bool {
+    tag.chars().any(char::is_uppercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{singular, LintReason, TagGroup};
+    use crate::note::Note;
+    use crate::tag::Tag;
+
+    fn note(tags: &[&str]) -> Note {
+        let tags = tags
+            .iter()
+            .map(|tag| Tag::Name(tag.to_string().into()))
+            .collect();
+        Note::cached(PathBuf::from("note.md"), tags, Vec::new(), None)
+    }
+
+    fn groups(notes: &[Note]) -> Vec<Vec<String>> {
+        TagGroup::lint(notes)
+            .iter()
+            .map(|group| group.tags().map(|(tag, _)| tag.to_owned()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn finds_the_singular() {
+        assert_eq!(singular("stories"), "story");
+        assert_eq!(singular("boxes"), "box");
+        assert_eq!(singular("matches"), "match");
+        assert_eq!(singular("notes"), "note");
+        assert_eq!(singular("class"), "class");
+        assert_eq!(singular("rust"), "rust");
+    }
+
+    #[test]
+    fn names_the_reason() {
+        assert_eq!(
+            LintReason::between("Rust", "rust"),
+            Some(LintReason::Casing)
+        );
+        assert_eq!(
+            LintReason::between("story", "Stories"),
+            Some(LintReason::Plural)
+        );
+        assert_eq!(
+            LintReason::between("meeting", "meetng"),
+            Some(LintReason::Typo)
+        );
+        assert_eq!(
+            LintReason::between("kubernetes", "kubernets"),
+            Some(LintReason::Typo)
+        );
+        assert_eq!(LintReason::between("rust", "just"), None);
+        assert_eq!(LintReason::between("go", "do"), None);
+        assert_eq!(LintReason::between("api", "app"), None);
+    }
+
+    #[test]
+    fn groups_similar_tags() {
+        let notes = [
+            note(&["meeting", "rust"]),
+            note(&["meeting"]),
+            note(&["Meeting", "meetings"]),
+        ];
+        let groups = groups(&notes);
+        assert_eq!(groups, [["meeting", "meetings", "Meeting"]]);
+    }
+
+    #[test]
+    fn does_not_chain_tags() {
+        let notes = [note(&["test", "tent", "tint"]), note(&["test"])];
+        let groups = groups(&notes);
+        assert_eq!(groups, [["test", "tent"]]);
+    }
+
+    #[test]
+    fn prefers_the_lowercase_spelling() {
+        let notes = [note(&["Meeting"]), note(&["meeting"])];
+        let mut groups = TagGroup::lint(&notes);
+        assert_eq!(groups[0].canonical(), "meeting");
+        assert!(groups[0].set_canonical("#Meeting"));
+        assert_eq!(groups[0].canonical(), "Meeting");
+        assert!(!groups[0].set_canonical("other"));
+    }
+}
@@ -17,12 +17,25 @@ mod carlender;
 mod code;
 mod commands;
 mod config;
+mod date;
+mod edit;
+mod graph;
+mod ignore;
+mod index;
+mod lint;
 mod name;
 mod note;
 mod output;
 mod person;
+mod query;
+mod rank;
+mod saved;
+mod stats;
 mod tag;
 mod todo;
+mod tree;
+mod vcard;
+mod vocabulary;
 
 fn main() -> eyre::Result<()> {
     let cli = Command::new(env!("CARGO_PKG_NAME"))
@@ -33,9 +46,7 @@ fn main() -> eyre::Result<()> {
         .termfmts()
         .get_matches();
 
-    commands::run(cli);
-
-    Ok(())
+    commands::run(cli)
 }
 
 fn is_valid_note_name(note_path: &Path) -> bool {
@@ -1,5 +1,5 @@
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use chrono::{Local, NaiveDate};
 use rand::distributions::Alphanumeric;
@@ -29,10 +29,14 @@ impl NameGenerator {
     }
 
     pub fn generate_with_date(&mut self, date: NaiveDate, config: &Config) -> PathBuf {
+        self.generate_in(config.path(), date)
+    }
+
+    pub fn generate_in(&mut self, directory: &Path, date: NaiveDate) -> PathBuf {
         loop {
             let note_name = generate_note_name(date);
 
-            let note_path = config.path().join(&note_name);
+            let note_path = directory.join(&note_name);
             if note_path.exists() {
                 continue;
             }
@@ -1,4 +1,4 @@
-use core::{slice, str};
+use std::cell::OnceCell;
 use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fmt::Write;
@@ -14,52 +14,105 @@ use nom::character::complete::{char, u16, u8};
 use nom::IResult;
 
 use crate::config::Config;
+use crate::ignore::IgnoreRules;
+use crate::index::Index;
 use crate::person::Person;
+use crate::query::{MatchMode, Query};
 use crate::tag::Tag;
+use crate::todo::Todo;
 
 #[derive(Clone)]
 pub struct Note {
     path: PathBuf,
-    content: String,
+    content: OnceCell<String>,
     tags: HashSet<Tag<'static>>,
+    todos: OnceCell<Vec<String>>,
+    date: OnceCell<Option<NaiveDate>>,
 }
 
 impl Note {
     pub fn search<'a>(
         config: &Config,
-        queries: &'a [&'a String],
+        query: &'a Query,
+        mode: MatchMode,
     ) -> eyre::Result<impl Iterator<Item = Self> + 'a> {
-        Ok(Self::all_notes(config)?
-            .filter(|note| queries.iter().all(|query| note.any_tag_contains(query))))
+        Ok(Self::all_notes(config)?.filter(move |note| query.matches(note, mode)))
     }
 
+    /// Load all notes using the index in the meta directory.
+    ///
+    /// Only notes which changed since the last run are parsed again.
     pub fn all_notes(config: &Config) -> eyre::Result<impl Iterator<Item = Note>> {
-        let result = fs::read_dir(config.path())?
-            .into_iter()
-            .filter_map(Result::ok)
-            .map(|entry| entry.path())
-            .filter(|entry| {
-                entry
-                    .extension()
-                    .map(|extension| extension == "md")
-                    .unwrap_or(false)
-            })
-            .flat_map(|path| Note::load(path).ok());
-        Ok(result)
+        let mut index = Index::load(config);
+        let notes = index.update(config)?;
+        Ok(notes.into_iter())
+    }
+
+    /// Find all notes in the notes directory and its subdirectories.
+    ///
+    /// Hidden files, the code, carlender and meta directories and paths
+    /// ignored by the `.scatternotesignore` file are skipped.
+    pub fn all_paths(config: &Config) -> eyre::Result<impl Iterator<Item = PathBuf>> {
+        let rules = IgnoreRules::load(config);
+        let mut paths = Vec::new();
+        let mut directories = vec![fs::read_dir(config.path())?];
+        while let Some(entries) = directories.pop() {
+            for entry in entries.filter_map(Result::ok) {
+                let path = entry.path();
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+                let relative = path.strip_prefix(config.path()).unwrap_or(&path);
+                if is_hidden || rules.is_ignored(relative, file_type.is_dir()) {
+                    continue;
+                }
+                if file_type.is_dir() {
+                    if config.is_special_path(&path) {
+                        continue;
+                    }
+                    if let Ok(entries) = fs::read_dir(&path) {
+                        directories.push(entries);
+                    }
+                } else if path.extension().is_some_and(|extension| extension == "md") {
+                    paths.push(path);
+                }
+            }
+        }
+        Ok(paths.into_iter())
     }
 
     pub fn load(path: PathBuf) -> eyre::Result<Self> {
         let content = read_to_string(&path)?;
-        let static_content =
-            str::from_utf8(unsafe { slice::from_raw_parts(content.as_ptr(), content.len()) })?;
-        let tags = Tag::all(static_content);
+        let tags = Tag::all(&content)
+            .into_iter()
+            .map(|tag| tag.into_owned())
+            .collect();
         Ok(Self {
             path,
-            content,
+            content: OnceCell::from(content),
             tags,
+            todos: OnceCell::new(),
+            date: OnceCell::new(),
         })
     }
 
+    /// Create a note from already parsed data. The content is read lazily.
+    pub fn cached(
+        path: PathBuf,
+        tags: HashSet<Tag<'static>>,
+        todos: Vec<String>,
+        date: Option<NaiveDate>,
+    ) -> Self {
+        Self {
+            path,
+            content: OnceCell::new(),
+            tags,
+            todos: OnceCell::from(todos),
+            date: OnceCell::from(date),
+        }
+    }
+
     pub fn edit(path: PathBuf, func: impl Fn(&mut String) -> bool) -> eyre::Result<()> {
         let mut file = OpenOptions::new().read(true).write(true).open(path)?;
         let mut b = String::new();
@@ -81,7 +134,12 @@ impl Note {
     }
 
     pub fn content(&self) -> &str {
-        &self.content
+        self.content
+            .get_or_init(|| read_to_string(&self.path).unwrap_or_default())
+    }
+
+    pub fn tags(&self) -> &HashSet<Tag<'static>> {
+        &self.tags
     }
 
     pub fn cloned_tags<'a>(&'a self) -> impl Iterator<Item = String> + 'a {
@@ -96,6 +154,15 @@ impl Note {
         self.tags.iter().any(|tag| tag.contains(name))
     }
 
+    /// The values of all `#key=value` tags with the given key.
+    pub fn attributes<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.tags
+            .iter()
+            .filter_map(|tag| tag.attribute())
+            .filter(move |(name, _)| *name == key)
+            .map(|(_, value)| value)
+    }
+
     pub fn join_tags(&self, separator: &str) -> eyre::Result<String> {
         join_tags_impl(self.tags.iter(), separator)
     }
@@ -116,12 +183,31 @@ impl Note {
     }
 
     pub fn parts(&self) -> impl Iterator<Item = &str> {
-        self.content
+        self.content()
             .split("\n\n")
             .map(|part| part.trim())
             .filter(|part| !part.is_empty())
     }
 
+    /// The parts of the note, which contain todo tags.
+    pub fn todo_parts(&self) -> &[String] {
+        self.todos.get_or_init(|| {
+            if !self.tags.iter().any(|tag| matches!(tag, Tag::Todo(_))) {
+                return Vec::new();
+            }
+            self.parts()
+                .filter(|part| Todo::parse_str(part).is_some())
+                .map(str::to_owned)
+                .collect()
+        })
+    }
+
+    pub fn date(&self, config: &Config) -> Option<NaiveDate> {
+        *self
+            .date
+            .get_or_init(|| Self::search_date(config, &self.path))
+    }
+
     /// Determine note date by searching in the following order:
     /// - in the note filename
     /// - the content of the file
@@ -1,13 +1,25 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::Display;
+use std::iter::once;
 use std::path::{Path, PathBuf};
 
+use chrono::NaiveDate;
+use itertools::Itertools;
 use serde::Serialize;
-use termfmt::{termarrow, termarrow_fg, termerr, termh1, terminfo, BundleFmt, Fg, TermFmt};
+use termfmt::{
+    termarrow, termarrow_fg, termerr, termh1, terminfo, BundleFmt, Fg, TermFmt, TermStyle,
+};
 
 use crate::config::Config;
+use crate::edit::LineChange;
+use crate::graph::{Graph, GraphFormat};
+use crate::lint::TagGroup;
 use crate::note::Note;
-use crate::person::Person;
+use crate::person::{Person, Profile};
+use crate::saved::SavedSearch;
+use crate::stats::TagStats;
+use crate::tag::HIERARCHY_SEPARATOR;
+use crate::tree::TagTree;
 
 use self::tags::pretty_print_with_tags;
 
@@ -23,6 +35,8 @@ pub struct DataBundle {
     #[serde(rename = "output", skip_serializing_if = "Vec::is_empty")]
     list_output: Vec<ListEntryFmt>,
     #[serde(rename = "output", skip_serializing_if = "Vec::is_empty")]
+    table_output: Vec<TableRowFmt>,
+    #[serde(rename = "output", skip_serializing_if = "Vec::is_empty")]
     command_output: Vec<String>,
     #[serde(rename = "remove", skip_serializing_if = "Vec::is_empty")]
     cleanup_remove_output: Vec<ListEntryFmt>,
@@ -30,6 +44,26 @@ pub struct DataBundle {
     cleanup_rename_output: Vec<PathBuf>,
     #[serde(rename = "todos", skip_serializing_if = "Vec::is_empty")]
     todos_output: Vec<TodoFmt>,
+    #[serde(rename = "timeline", skip_serializing_if = "Vec::is_empty")]
+    timeline_output: Vec<TimelineFmt>,
+    #[serde(rename = "searches", skip_serializing_if = "Vec::is_empty")]
+    saved_searches_output: Vec<SavedSearchFmt>,
+    #[serde(rename = "tags", skip_serializing_if = "Vec::is_empty")]
+    tag_stats_output: Vec<TagStats>,
+    #[serde(rename = "groups", skip_serializing_if = "Vec::is_empty")]
+    tag_groups_output: Vec<TagGroup>,
+    #[serde(rename = "tags", skip_serializing_if = "Option::is_none")]
+    tag_tree_output: Option<TagTree>,
+    #[serde(rename = "graph", skip_serializing_if = "Option::is_none")]
+    graph_output: Option<Graph>,
+    #[serde(rename = "connections", skip_serializing_if = "Vec::is_empty")]
+    connections_output: Vec<ConnectionsFmt>,
+    #[serde(rename = "changes", skip_serializing_if = "Vec::is_empty")]
+    diff_output: Vec<DiffFmt>,
+    #[serde(rename = "person", skip_serializing_if = "Option::is_none")]
+    profile_output: Option<ProfileFmt>,
+    #[serde(rename = "variants", skip_serializing_if = "Vec::is_empty")]
+    person_variants_output: Vec<PersonVariantsFmt>,
     #[serde(rename = "persons", skip_serializing_if = "HashSet::is_empty")]
     persons_output: HashSet<Person<'static>>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -43,16 +77,71 @@ pub struct DataBundle {
 #[derive(Serialize)]
 pub struct ListEntryFmt {
     file: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<NaiveDate>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    snippets: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TableRowFmt {
+    file: PathBuf,
+    attributes: BTreeMap<String, String>,
 }
 
 #[derive(Serialize)]
 pub struct TodoFmt {
     file: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<NaiveDate>,
     content: String,
 }
 
+#[derive(Serialize)]
+pub struct DiffFmt {
+    file: PathBuf,
+    lines: Vec<LineChange>,
+}
+
+#[derive(Serialize)]
+pub struct PersonVariantsFmt {
+    handle: String,
+    variants: BTreeMap<String, usize>,
+}
+
+#[derive(Serialize)]
+pub struct ProfileFmt {
+    handle: String,
+    #[serde(flatten)]
+    profile: Profile,
+}
+
+#[derive(Serialize)]
+pub struct TimelineFmt {
+    file: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<NaiveDate>,
+    parts: Vec<String>,
+    todos: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ConnectionsFmt {
+    node: String,
+    connections: Vec<(String, usize)>,
+}
+
+#[derive(Serialize)]
+pub struct SavedSearchFmt {
+    name: String,
+    queries: Vec<String>,
+    options: Vec<String>,
+}
+
 impl BundleFmt for DataBundle {
     type Config = Config;
 
@@ -81,11 +170,29 @@ pub trait OutputFmt {
     fn headline(&mut self, value: impl Display);
     fn command(&mut self, value: &str);
     fn file(&mut self, file: impl AsRef<Path>);
-    fn list(&mut self, note: &Note, with_tags: bool);
+    fn list(&mut self, note: &Note, with_tags: bool, date: Option<NaiveDate>, score: Option<f64>);
+    fn snippets(&mut self, snippets: &[&str]);
+    fn attribute_table(&mut self, keys: &[String], notes: &[&Note]);
     fn cleanup_remove(&mut self, note: &Note, with_tags: bool);
     fn cleanup_rename(&mut self, note: &Note);
-    fn todo(&mut self, file: impl AsRef<Path>, content: &str);
+    fn todo(&mut self, file: impl AsRef<Path>, date: Option<NaiveDate>, content: &str);
+    fn timeline(
+        &mut self,
+        file: impl AsRef<Path>,
+        date: Option<NaiveDate>,
+        parts: &[&str],
+        todos: &[&str],
+    );
+    fn saved_search(&mut self, name: &str, search: &SavedSearch);
+    fn tag_stats(&mut self, stats: &TagStats);
+    fn tag_tree(&mut self, tree: &TagTree);
+    fn tag_group(&mut self, group: &TagGroup);
+    fn diff(&mut self, file: impl AsRef<Path>, changes: &[LineChange]);
+    fn graph(&mut self, graph: &Graph, format: GraphFormat);
+    fn connections(&mut self, node: &str, connections: &[(&str, usize)]);
     fn persons(&mut self, persons: &HashSet<Person<'static>>);
+    fn profile(&mut self, handle: &str, profile: &Profile);
+    fn person_variants(&mut self, handle: &str, variants: &BTreeMap<String, usize>);
     fn command_output(&mut self, output: &str);
     fn end(&mut self);
 }
@@ -141,27 +248,127 @@ impl OutputFmt for TermFmt<DataBundle> {
         }
     }
 
-    fn list(&mut self, note: &Note, with_tags: bool) {
+    fn list(&mut self, note: &Note, with_tags: bool, date: Option<NaiveDate>, score: Option<f64>) {
         self.bundle(|bundle| {
             bundle.list_output.push(ListEntryFmt {
                 file: note.path().to_owned(),
+                date,
                 tags: with_tags
                     .then(|| note.cloned_tags().collect())
                     .unwrap_or_default(),
+                score,
+                snippets: Vec::new(),
             })
         });
         if self.is_plain() {
             print!("{}", note.path().display());
+            if let Some(date) = date {
+                print!("|{}", date);
+            }
             if with_tags {
                 print!("|{}", note.join_tags(",").unwrap());
             }
+            if let Some(score) = score {
+                print!("|{:.3}", score);
+            }
             println!();
         }
         if self.is_interactive() {
             termh1(note.path().display());
+            if let Some(date) = date {
+                termarrow(date);
+            }
             if with_tags {
                 termarrow(note.join_tags(", ").unwrap());
             }
+            if let Some(score) = score {
+                termarrow(format_args!("score {:.3}", score));
+            }
+        }
+    }
+
+    /// Print the snippets of the note listed last.
+    fn snippets(&mut self, snippets: &[&str]) {
+        self.bundle(|bundle| {
+            if let Some(entry) = bundle.list_output.last_mut() {
+                entry
+                    .snippets
+                    .extend(snippets.iter().map(|snippet| snippet.to_string()));
+            }
+        });
+        if self.is_plain() {
+            for line in snippets.iter().flat_map(|snippet| snippet.lines()) {
+                println!("snippet|{}", line);
+            }
+        }
+        if self.is_interactive() {
+            for snippet in snippets {
+                println!();
+                pretty_print_with_tags(snippet);
+            }
+        }
+    }
+
+    /// Print the notes as rows with the values of the attributes as columns.
+    fn attribute_table(&mut self, keys: &[String], notes: &[&Note]) {
+        let rows: Vec<(String, Vec<String>)> = notes
+            .iter()
+            .map(|note| {
+                let values = keys
+                    .iter()
+                    .map(|key| note.attributes(key).sorted().join(","))
+                    .collect();
+                (note.path().display().to_string(), values)
+            })
+            .collect();
+        self.bundle(|bundle| {
+            for (note, (_, values)) in notes.iter().zip(&rows) {
+                bundle.table_output.push(TableRowFmt {
+                    file: note.path().to_owned(),
+                    attributes: keys
+                        .iter()
+                        .cloned()
+                        .zip(values.iter().cloned())
+                        .filter(|(_, value)| !value.is_empty())
+                        .collect(),
+                })
+            }
+        });
+        if self.is_plain() {
+            println!("file|{}", keys.join("|"));
+            for (file, values) in &rows {
+                println!("{}|{}", file, values.join("|"));
+            }
+        }
+        if self.is_interactive() {
+            let header = once("file").chain(keys.iter().map(String::as_str));
+            let widths: Vec<usize> = header
+                .clone()
+                .enumerate()
+                .map(|(index, name)| {
+                    rows.iter()
+                        .map(|(file, values)| match index {
+                            0 => file.chars().count(),
+                            index => values[index - 1].chars().count(),
+                        })
+                        .chain([name.chars().count()])
+                        .max()
+                        .unwrap_or_default()
+                })
+                .collect();
+            let header = header
+                .zip(&widths)
+                .map(|(name, width)| format!("{:width$}", name, width = width))
+                .join("  ");
+            println!("{}", header.bold());
+            for (file, values) in &rows {
+                let row = once(file)
+                    .chain(values)
+                    .zip(&widths)
+                    .map(|(value, width)| format!("{:width$}", value, width = width))
+                    .join("  ");
+                println!("{}", row.trim_end());
+            }
         }
     }
 
@@ -169,9 +376,12 @@ impl OutputFmt for TermFmt<DataBundle> {
         self.bundle(|bundle| {
             bundle.cleanup_remove_output.push(ListEntryFmt {
                 file: note.path().to_owned(),
+                date: None,
                 tags: with_tags
                     .then(|| note.cloned_tags().collect())
                     .unwrap_or_default(),
+                score: None,
+                snippets: Vec::new(),
             })
         });
         if self.is_plain() {
@@ -194,23 +404,233 @@ impl OutputFmt for TermFmt<DataBundle> {
         }
     }
 
-    fn todo(&mut self, file: impl AsRef<Path>, content: &str) {
+    fn todo(&mut self, file: impl AsRef<Path>, date: Option<NaiveDate>, content: &str) {
         self.bundle(|bundle| {
             bundle.todos_output.push(TodoFmt {
                 file: file.as_ref().to_owned(),
+                date,
                 content: content.to_owned(),
             })
         });
         if self.is_plain() {
-            println!("{}", file.as_ref().display());
+            print!("{}", file.as_ref().display());
+            if let Some(date) = date {
+                print!("|{}", date);
+            }
+            println!();
             println!("{}", content);
         }
         if self.is_interactive() {
             termh1(file.as_ref().display());
+            if let Some(date) = date {
+                termarrow(date);
+            }
             pretty_print_with_tags(content);
         }
     }
 
+    /// Print a note of a timeline with its matching parts and open todos.
+    fn timeline(
+        &mut self,
+        file: impl AsRef<Path>,
+        date: Option<NaiveDate>,
+        parts: &[&str],
+        todos: &[&str],
+    ) {
+        self.bundle(|bundle| {
+            bundle.timeline_output.push(TimelineFmt {
+                file: file.as_ref().to_owned(),
+                date,
+                parts: parts.iter().map(|part| part.to_string()).collect(),
+                todos: todos.iter().map(|todo| todo.to_string()).collect(),
+            })
+        });
+        let date = date
+            .map(|date| date.to_string())
+            .unwrap_or_else(|| "no date".to_owned());
+        if self.is_plain() {
+            println!("{}|{}", date, file.as_ref().display());
+            for part in parts {
+                println!("{}", part);
+            }
+            for todo in todos {
+                println!("todo|{}", todo);
+            }
+        }
+        if self.is_interactive() {
+            termh1(date);
+            termarrow(file.as_ref().display());
+            for part in parts {
+                println!();
+                pretty_print_with_tags(part);
+            }
+            for todo in todos {
+                println!();
+                print!("{} ", "open".fg_yellow());
+                pretty_print_with_tags(todo);
+            }
+        }
+    }
+
+    fn saved_search(&mut self, name: &str, search: &SavedSearch) {
+        self.bundle(|bundle| {
+            bundle.saved_searches_output.push(SavedSearchFmt {
+                name: name.to_owned(),
+                queries: search.queries.clone(),
+                options: search.options.clone(),
+            })
+        });
+        let args = search.queries.iter().chain(&search.options).join(" ");
+        if self.is_plain() {
+            println!("{}|{}", name, args);
+        }
+        if self.is_interactive() {
+            termh1(name);
+            termarrow(args);
+        }
+    }
+
+    fn tag_stats(&mut self, stats: &TagStats) {
+        self.bundle(|bundle| bundle.tag_stats_output.push(stats.clone()));
+        let last_used = stats
+            .last_used()
+            .map(|date| date.to_string())
+            .unwrap_or_default();
+        if self.is_plain() {
+            let co_occurring = stats
+                .co_occurring()
+                .map(|(tag, count)| format!("{}:{}", tag, count))
+                .join(",");
+            println!(
+                "{}|{}|{}|{}",
+                stats.tag(),
+                stats.count(),
+                last_used,
+                co_occurring
+            );
+        }
+        if self.is_interactive() {
+            print!("{} {}", stats.tag().fg_blue(), stats.count());
+            if !last_used.is_empty() {
+                print!(" (last used {})", last_used);
+            }
+            println!();
+            let co_occurring = stats
+                .co_occurring()
+                .map(|(tag, count)| format!("{} {}", tag, count))
+                .join(", ");
+            if !co_occurring.is_empty() {
+                termarrow(co_occurring);
+            }
+        }
+    }
+
+    fn tag_tree(&mut self, tree: &TagTree) {
+        self.bundle(|bundle| bundle.tag_tree_output = Some(tree.clone()));
+        if self.is_plain() {
+            visit_tag_tree(tree, "", 0, &|path, _, _, node| {
+                println!("#{}|{}", path, node.count())
+            });
+        }
+        if self.is_interactive() {
+            visit_tag_tree(tree, "", 0, &|_, name, depth, node| {
+                let tag = format!("#{}", name);
+                println!("{}{} {}", "  ".repeat(depth), tag.fg_blue(), node.count());
+            });
+        }
+    }
+
+    fn tag_group(&mut self, group: &TagGroup) {
+        self.bundle(|bundle| bundle.tag_groups_output.push(group.clone()));
+        let reasons = group.reasons().iter().map(|reason| reason.text()).join(",");
+        if self.is_plain() {
+            let tags = group
+                .tags()
+                .map(|(tag, count)| format!("{}:{}", tag, count))
+                .join(",");
+            println!("{}|{}", tags, reasons);
+        }
+        if self.is_interactive() {
+            let tags = group
+                .tags()
+                .map(|(tag, count)| format!("{} {}", format_args!("#{}", tag).fg_blue(), count))
+                .join(", ");
+            termarrow(format_args!("{} ({})", tags, reasons));
+        }
+    }
+
+    fn diff(&mut self, file: impl AsRef<Path>, changes: &[LineChange]) {
+        self.bundle(|bundle| {
+            bundle.diff_output.push(DiffFmt {
+                file: file.as_ref().to_owned(),
+                lines: changes.to_vec(),
+            })
+        });
+        if self.is_plain() {
+            println!("{}", file.as_ref().display());
+            for change in changes {
+                println!("{}-{}", change.line(), change.before());
+                println!("{}+{}", change.line(), change.after());
+            }
+        }
+        if self.is_interactive() {
+            termh1(file.as_ref().display());
+            for change in changes {
+                println!(
+                    "{:>4} {}",
+                    change.line(),
+                    format!("- {}", change.before()).fg_red()
+                );
+                println!(
+                    "{:>4} {}",
+                    change.line(),
+                    format!("+ {}", change.after()).fg_green()
+                );
+            }
+        }
+    }
+
+    fn graph(&mut self, graph: &Graph, format: GraphFormat) {
+        self.bundle(|bundle| bundle.graph_output = Some(graph.clone()));
+        if self.is_plain() || self.is_interactive() {
+            match graph.render(format) {
+                Ok(content) => println!("{}", content),
+                Err(error) => self.error(error),
+            }
+        }
+    }
+
+    /// Print the nodes connected to a node of a graph with their weights.
+    fn connections(&mut self, node: &str, connections: &[(&str, usize)]) {
+        self.bundle(|bundle| {
+            bundle.connections_output.push(ConnectionsFmt {
+                node: node.to_owned(),
+                connections: connections
+                    .iter()
+                    .map(|(node, weight)| (node.to_string(), *weight))
+                    .collect(),
+            })
+        });
+        if self.is_plain() {
+            let connections = connections
+                .iter()
+                .map(|(node, weight)| format!("{}:{}", node, weight))
+                .join(",");
+            println!("{}|{}", node, connections);
+        }
+        if self.is_interactive() {
+            println!("{}", node.fg_blue());
+            if !connections.is_empty() {
+                termarrow(
+                    connections
+                        .iter()
+                        .map(|(node, weight)| format!("{} {}", node, weight))
+                        .join(", "),
+                );
+            }
+        }
+    }
+
     fn persons(&mut self, persons: &HashSet<Person<'static>>) {
         self.bundle(|bundle| bundle.persons_output.extend(persons.clone()));
         if self.is_plain() {
@@ -231,6 +651,74 @@ impl OutputFmt for TermFmt<DataBundle> {
         }
     }
 
+    fn profile(&mut self, handle: &str, profile: &Profile) {
+        self.bundle(|bundle| {
+            bundle.profile_output = Some(ProfileFmt {
+                handle: handle.to_owned(),
+                profile: profile.clone(),
+            })
+        });
+        let fields = [
+            ("name", profile.name.clone()),
+            (
+                "aliases",
+                Some(profile.aliases.join(", ")).filter(|aliases| !aliases.is_empty()),
+            ),
+            ("email", profile.email.clone()),
+            ("team", profile.team.clone()),
+        ];
+        if self.is_plain() {
+            println!("@{}", handle);
+            for (field, value) in &fields {
+                if let Some(value) = value {
+                    println!("{}|{}", field, value);
+                }
+            }
+            if let Some(notes) = &profile.notes {
+                println!("{}", notes);
+            }
+        }
+        if self.is_interactive() {
+            termh1(format_args!("@{}", handle));
+            for (field, value) in &fields {
+                if let Some(value) = value {
+                    termarrow(format_args!("{}: {}", field, value));
+                }
+            }
+            if let Some(notes) = &profile.notes {
+                println!();
+                pretty_print_with_tags(notes);
+            }
+        }
+    }
+
+    fn person_variants(&mut self, handle: &str, variants: &BTreeMap<String, usize>) {
+        self.bundle(|bundle| {
+            bundle.person_variants_output.push(PersonVariantsFmt {
+                handle: handle.to_owned(),
+                variants: variants.clone(),
+            })
+        });
+        if self.is_plain() {
+            let variants = variants
+                .iter()
+                .map(|(name, count)| format!("{}:{}", name, count))
+                .join(",");
+            println!("{}|{}", handle, variants);
+        }
+        if self.is_interactive() {
+            let variants = variants
+                .iter()
+                .map(|(name, count)| format!("@{} {}", name, count))
+                .join(", ");
+            termarrow(format_args!(
+                "{} <- {}",
+                format_args!("@{}", handle).fg_blue(),
+                variants
+            ));
+        }
+    }
+
     fn command_output(&mut self, output: &str) {
         if self.is_plain() || self.is_interactive() {
             println!("{}", output);
@@ -243,3 +731,19 @@ impl OutputFmt for TermFmt<DataBundle> {
         }
     }
 }
+
+fn visit_tag_tree(
+    tree: &TagTree,
+    parent: &str,
+    depth: usize,
+    visit: &impl Fn(&str, &str, usize, &TagTree),
+) {
+    for (name, node) in tree.children() {
+        let path = match parent {
+            "" => name.to_owned(),
+            parent => format!("{}{}{}", parent, HIERARCHY_SEPARATOR, name),
+        };
+        visit(&path, name, depth, node);
+        visit_tag_tree(node, &path, depth + 1, visit);
+    }
+}
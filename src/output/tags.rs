@@ -5,13 +5,15 @@ use crate::tag::Tag;
 pub fn pretty_print_with_tags(mut content: &str) {
     while !content.is_empty() {
         match Tag::parse_next(content) {
-            Ok((preceding, remaining, tag)) => {
+            Ok((preceding, remaining, _)) => {
+                // the tag as written in the note, not the canonical keyword
+                let tag = &content[preceding.len()..content.len() - remaining.len()];
                 content = remaining;
-                print!("{}{}", preceding, format_args!("{}", tag).fg_blue());
+                print!("{}{}", preceding, tag.fg_blue());
             }
-            Err(remaining) => {
-                print!("{}", remaining);
-                break;
+            Err((skipped, remaining)) => {
+                content = remaining;
+                print!("{}", skipped);
             }
         }
     }
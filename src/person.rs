@@ -1,24 +1,50 @@
 use std::borrow::Cow;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::Display;
-use std::fs::{self, File, OpenOptions};
-use std::io::{self, BufWriter, Read, Write};
+use std::fs;
 use std::path::PathBuf;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
 use crate::note::Note;
+use crate::tag::{iter_non_code_blocks, Tag};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Person<'a> {
     name: Cow<'a, str>,
 }
 
+/// The profiles of the persons stored in the meta directory, keyed by the
+/// handle used in the notes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Profiles {
+    persons: BTreeMap<String, Profile>,
+}
+
+/// What is known about a person. Aliases are other handles, which are
+/// replaced with the handle of the person when parsing the notes.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
 impl Person<'static> {
     pub fn load_all_persons(config: &Config) -> eyre::Result<HashSet<Self>> {
-        let content = fs::read_to_string(persons_meta_file(config))?;
-        Ok(Self::load_all_persons_from(&content))
+        let profiles = Profiles::load(config)?;
+        Ok(profiles
+            .iter()
+            .map(|(handle, _)| Self::new(handle.to_owned()))
+            .collect())
     }
 
     pub fn load_all_persons_from(content: &str) -> HashSet<Self> {
@@ -27,7 +53,7 @@ impl Person<'static> {
             .filter(|line| !line.is_empty() || !line.starts_with("#"))
             .flat_map(|line| {
                 line.trim()
-                    .split(|char| !matches!(char, 'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '_'))
+                    .split(|char: char| !char.is_alphanumeric() && !matches!(char, '-' | '_'))
                     .filter(|name| !name.is_empty())
             })
             .map(|name| Self::new(name.to_owned()))
@@ -44,21 +70,167 @@ impl Person<'static> {
         Ok(persons)
     }
 
+    /// Add a profile for every new person.
     pub fn save_persons<'a>(config: &Config, persons: &HashSet<Person<'a>>) -> eyre::Result<()> {
-        let mut file = OpenOptions::new()
-            .create(true)
-            .read(true)
-            .append(true)
-            .open(persons_meta_file(config))?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
-        let previous = Self::load_all_persons_from(&content);
-        let mut writer = BufWriter::new(file);
-        for person in persons.difference(&previous) {
-            write!(writer, "\n{}", person.name())?;
+        let mut profiles = Profiles::load(config)?;
+        for person in persons {
+            profiles.insert_missing(person.name());
+        }
+        profiles.save(config)
+    }
+}
+
+impl Profiles {
+    /// Load the profiles. The handles of an old `persons.txt` file are used if
+    /// there are no profiles yet.
+    pub fn load(config: &Config) -> eyre::Result<Self> {
+        let path = profiles_meta_file(config);
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            return Ok(serde_json::from_str(&content)?);
         }
+        let mut profiles = Self::default();
+        if let Ok(content) = fs::read_to_string(persons_meta_file(config)) {
+            for person in Person::load_all_persons_from(&content) {
+                profiles.insert_missing(&person.name().to_lowercase());
+            }
+        }
+        Ok(profiles)
+    }
+
+    pub fn save(&self, config: &Config) -> eyre::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(profiles_meta_file(config), content)?;
         Ok(())
     }
+
+    /// Find the profile by the handle or one of the aliases of the person.
+    pub fn get(&self, name: &str) -> Option<(&str, &Profile)> {
+        let name = name.trim_start_matches('@').to_lowercase();
+        if let Some((handle, profile)) = self.persons.get_key_value(&name) {
+            return Some((handle, profile));
+        }
+        self.persons
+            .iter()
+            .find(|(_, profile)| profile.has_alias(&name))
+            .map(|(handle, profile)| (handle.as_str(), profile))
+    }
+
+    /// Add an empty profile if the person is not known yet.
+    pub fn insert_missing(&mut self, handle: &str) {
+        if self.get(handle).is_none() {
+            self.persons.insert(handle.to_owned(), Profile::default());
+        }
+    }
+
+    /// Add the persons of a vCard import or update the known persons, which
+    /// are found by their handle, an alias or the e-mail.
+    /// Returns the handles of the added and of the updated persons.
+    pub fn import(&mut self, persons: Vec<(String, Profile)>) -> (Vec<String>, Vec<String>) {
+        let mut added = Vec::new();
+        let mut updated = Vec::new();
+        for (handle, profile) in persons {
+            let known = self
+                .get(&handle)
+                .or_else(|| {
+                    let email = profile.email.as_deref()?;
+                    self.iter()
+                        .find(|(_, known)| known.email.as_deref() == Some(email))
+                        .map(|(handle, known)| (handle.as_str(), known))
+                })
+                .map(|(handle, _)| handle.to_owned());
+            match known {
+                Some(known) => {
+                    let mut profile = profile;
+                    if known != handle {
+                        profile.aliases.push(handle);
+                    }
+                    self.update(&known, profile);
+                    updated.push(known);
+                }
+                None => {
+                    self.update(&handle, profile);
+                    added.push(handle);
+                }
+            }
+        }
+        (added, updated)
+    }
+
+    /// Merge the profile into the profile of the person.
+    pub fn update(&mut self, handle: &str, profile: Profile) {
+        self.persons
+            .entry(handle.to_owned())
+            .or_default()
+            .merge(profile);
+    }
+
+    /// The spellings of the mentioned persons, which differ from their
+    /// handle, with the number of notes using them. Persons without a
+    /// profile are written like most notes write them, preferring lowercase.
+    pub fn variants<'a>(
+        &self,
+        contents: impl IntoIterator<Item = &'a str>,
+    ) -> BTreeMap<String, BTreeMap<String, usize>> {
+        let mut spellings: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+        for content in contents {
+            let mentions: HashSet<Person> = Person::mentions(content).into_iter().collect();
+            for mention in mentions {
+                let name = mention.name();
+                let handle = match self.get(name) {
+                    Some((handle, _)) => handle.to_owned(),
+                    None => name.to_lowercase(),
+                };
+                let counts = spellings.entry(handle).or_default();
+                *counts.entry(name.to_owned()).or_insert(0) += 1;
+            }
+        }
+        spellings
+            .into_iter()
+            .filter_map(|(handle, mut counts)| {
+                let handle = if self.persons.contains_key(&handle) {
+                    handle
+                } else {
+                    let (name, _) = counts.iter().min_by_key(|(name, count)| {
+                        let uppercase = name.chars().any(char::is_uppercase);
+                        (std::cmp::Reverse(**count), uppercase, name.as_str())
+                    })?;
+                    name.clone()
+                };
+                counts.remove(&handle);
+                (!counts.is_empty()).then_some((handle, counts))
+            })
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Profile)> {
+        self.persons.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.persons.is_empty()
+    }
+}
+
+impl Profile {
+    /// Take over the fields set in the other profile and add its aliases.
+    pub fn merge(&mut self, other: Profile) {
+        self.name = other.name.or(self.name.take());
+        self.email = other.email.or(self.email.take());
+        self.team = other.team.or(self.team.take());
+        self.notes = other.notes.or(self.notes.take());
+        for alias in other.aliases {
+            if !self.has_alias(&alias.trim_start_matches('@').to_lowercase()) {
+                self.aliases.push(alias);
+            }
+        }
+    }
+
+    pub fn has_alias(&self, name: &str) -> bool {
+        self.aliases
+            .iter()
+            .any(|alias| alias.trim_start_matches('@').to_lowercase() == name)
+    }
 }
 
 impl<'a> Person<'a> {
@@ -66,12 +238,37 @@ impl<'a> Person<'a> {
         Self { name: name.into() }
     }
 
+    /// The persons mentioned in the content as they are written.
+    pub fn mentions(content: &'a str) -> Vec<Self> {
+        let mut mentions = Vec::new();
+        for mut input in iter_non_code_blocks(content) {
+            while !input.is_empty() {
+                match Tag::parse_next(input) {
+                    Ok((_, remaining, tag)) => {
+                        input = remaining;
+                        if let Tag::Person(person) = tag {
+                            mentions.push(person);
+                        }
+                    }
+                    Err((_, remaining)) => input = remaining,
+                }
+            }
+        }
+        mentions
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// The name in lowercase, which is how the handles of the profiles are
+    /// written.
+    pub fn handle(&self) -> String {
+        self.name.to_lowercase()
+    }
+
     pub fn into_owned(&self) -> Person<'static> {
-        let name = self.name.clone().to_lowercase();
+        let name = self.name.to_lowercase();
         Person {
             name: Cow::from(name),
         }
@@ -85,22 +282,69 @@ impl<'a> From<&'a str> for Person<'a> {
 }
 
 impl<'a> From<String> for Person<'a> {
-    fn from(mut value: String) -> Self {
-        value.make_ascii_lowercase();
-        Person::new(value)
+    fn from(value: String) -> Self {
+        Person::new(value.to_lowercase())
     }
 }
 
 impl<'a> Display for Person<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (first, text) = self.name.split_at(1);
-        if let Some(first) = first.chars().next() {
-            write!(f, "{}", first.to_ascii_uppercase())?;
+        let mut chars = self.name.chars();
+        if let Some(first) = chars.next() {
+            write!(f, "{}", first.to_uppercase())?;
         }
-        write!(f, "{}", text)
+        write!(f, "{}", chars.as_str())
     }
 }
 
 fn persons_meta_file(config: &Config) -> PathBuf {
     config.meta("persons.txt")
 }
+
+fn profiles_meta_file(config: &Config) -> PathBuf {
+    config.meta("persons.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Profile, Profiles};
+
+    #[test]
+    fn finds_the_variants_of_persons() {
+        let mut profiles = Profiles::default();
+        profiles.update(
+            "alice",
+            Profile {
+                aliases: vec!["ali".to_owned()],
+                ..Profile::default()
+            },
+        );
+        let notes = [
+            "@Alice and @ali met @BobSmith",
+            "@BobSmith and @alice",
+            "@bobsmith and @Carol",
+        ];
+        let variants = profiles.variants(notes);
+        let variants: Vec<(&str, Vec<(&str, usize)>)> = variants
+            .iter()
+            .map(|(handle, names)| {
+                let names = names.iter().map(|(name, count)| (name.as_str(), *count));
+                (handle.as_str(), names.collect())
+            })
+            .collect();
+        assert_eq!(
+            variants,
+            [
+                ("BobSmith", vec![("bobsmith", 1)]),
+                ("alice", vec![("Alice", 1), ("ali", 1)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn prefers_the_lowercase_spelling_of_unknown_persons() {
+        let variants = Profiles::default().variants(["@Carol", "@carol"]);
+        assert_eq!(variants.len(), 1);
+        assert_eq!(variants["carol"].keys().collect::<Vec<_>>(), ["Carol"]);
+    }
+}
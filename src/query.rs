@@ -0,0 +1,419 @@
+use std::cmp::Ordering;
+
+use chrono::{Local, NaiveDate};
+use clap::ValueEnum;
+use eyre::eyre;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while, take_while1};
+use nom::character::complete::{char, multispace0};
+use nom::combinator::{all_consuming, map, opt, verify};
+use nom::multi::many0;
+use nom::sequence::{delimited, pair, preceded, tuple};
+use nom::IResult;
+use serde::{Deserialize, Serialize};
+
+use crate::date::DateRange;
+use crate::note::Note;
+use crate::tag::{is_tag_char, Tag};
+use crate::vocabulary::Vocabulary;
+
+const KEYWORDS: [&str; 3] = ["AND", "OR", "NOT"];
+
+/// A boolean search query over the tags of a note.
+///
+/// Terms next to each other are combined with `AND`. Terms can be combined
+/// with `AND` (`&`), `OR` (`|`) and negated with `NOT` (`!`). Parentheses
+/// group terms and quoted phrases are taken literally.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Term(Term),
+    Not(Box<Query>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+impl Query {
+    pub fn parse(input: &str) -> eyre::Result<Self> {
+        if input.trim().is_empty() {
+            return Ok(Self::And(Vec::new()));
+        }
+        let mut parser = all_consuming(delimited(multispace0, expression, multispace0));
+        parser(input)
+            .map(|(_, query)| query)
+            .map_err(|_| eyre!("invalid search query: {}", input))
+    }
+
+    pub fn matches(&self, note: &Note, mode: MatchMode) -> bool {
+        match self {
+            Self::Term(term) => note.tags().iter().any(|tag| term.matches(tag, mode)),
+            Self::Not(query) => !query.matches(note, mode),
+            Self::And(queries) => queries.iter().all(|query| query.matches(note, mode)),
+            Self::Or(queries) => queries.iter().any(|query| query.matches(note, mode)),
+        }
+    }
+
+    /// The terms of the query, which are not negated.
+    pub fn terms(&self) -> Vec<&Term> {
+        match self {
+            Self::Term(term) => vec![term],
+            Self::Not(_) => Vec::new(),
+            Self::And(queries) | Self::Or(queries) => {
+                queries.iter().flat_map(|query| query.terms()).collect()
+            }
+        }
+    }
+
+    /// The parts of the note containing tags matched by any term of the query.
+    pub fn matching_parts<'a>(&self, note: &'a Note, mode: MatchMode) -> Vec<&'a str> {
+        let terms = self.terms();
+        note.parts()
+            .filter(|part| {
+                Tag::all(part)
+                    .iter()
+                    .any(|tag| terms.iter().any(|term| term.matches(tag, mode)))
+            })
+            .collect()
+    }
+
+    /// The number of tags of the note matched by any term of the query.
+    pub fn count_matches(&self, note: &Note, mode: MatchMode) -> usize {
+        let terms = self.terms();
+        note.tags()
+            .iter()
+            .filter(|tag| terms.iter().any(|term| term.matches(tag, mode)))
+            .count()
+    }
+}
+
+/// A single term of a query.
+///
+/// Terms with a tag prefix (`#`, `@` or `~`) only match tags of the same kind,
+/// bare words match any tag. Comparisons like `priority=high` or `estimate>3`
+/// match `#key=value` tags.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Any(String),
+    Tag(Tag<'static>),
+    Compare(Comparison),
+}
+
+/// Compares the value of `#key=value` tags with the given key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    key: String,
+    operator: Operator,
+    value: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Term {
+    pub fn parse(word: &str) -> Self {
+        Self::parse_with(word, Vocabulary::get())
+    }
+
+    /// Parse the term and map aliases to their canonical tag. Bare words are
+    /// mapped like name tags, because the tags of the notes are stored in
+    /// their canonical form.
+    fn parse_with(word: &str, vocabulary: &Vocabulary) -> Self {
+        if let Some(comparison) = Comparison::parse(word) {
+            return Self::Compare(comparison);
+        }
+        match Tag::parse_next(word) {
+            Ok(("", "", tag)) => Self::Tag(vocabulary.canonical(tag).into_owned()),
+            _ => match vocabulary.canonical(Tag::Name(word.into())) {
+                Tag::Name(name) => Self::Any(name.into_owned()),
+                _ => Self::Any(word.to_owned()),
+            },
+        }
+    }
+
+    pub fn matches(&self, tag: &Tag, mode: MatchMode) -> bool {
+        match (self, tag) {
+            (Self::Any(text), tag) => tag.matches(text, mode),
+            (Self::Tag(Tag::Name(text)), Tag::Name(_)) => tag.matches(text, mode),
+            (Self::Tag(Tag::Person(person)), Tag::Person(_)) => tag.matches(person.name(), mode),
+            (Self::Tag(term), tag) => term == tag,
+            (Self::Compare(comparison), tag) => tag
+                .attribute()
+                .is_some_and(|(key, value)| comparison.matches(key, value)),
+        }
+    }
+}
+
+impl Comparison {
+    /// Parse a comparison like `priority=high`, `estimate>3` or
+    /// `due<2026-11-01`. Attribute tags like `#priority=high` are compared
+    /// for equality as well.
+    pub fn parse(word: &str) -> Option<Self> {
+        let word = word.strip_prefix('#').unwrap_or(word);
+        let index = word.find(['=', '!', '<', '>'])?;
+        let (key, rest) = word.split_at(index);
+        let (operator, value) = [
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessOrEqual),
+            (">=", Operator::GreaterOrEqual),
+            ("=", Operator::Equal),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+        ]
+        .into_iter()
+        .find_map(|(symbol, operator)| Some((operator, rest.strip_prefix(symbol)?)))?;
+        if key.is_empty() || value.is_empty() || !key.chars().all(is_tag_char) {
+            return None;
+        }
+        Some(Self {
+            key: key.to_owned(),
+            operator,
+            value: value.to_owned(),
+        })
+    }
+
+    /// Compare the value of the attribute as number, as date or as text.
+    pub fn matches(&self, key: &str, value: &str) -> bool {
+        if key != self.key {
+            return false;
+        }
+        let ordering = compare_numbers(value, &self.value)
+            .or_else(|| compare_dates(value, &self.value))
+            .unwrap_or_else(|| value.to_lowercase().cmp(&self.value.to_lowercase()));
+        match self.operator {
+            Operator::Equal => ordering.is_eq(),
+            Operator::NotEqual => ordering.is_ne(),
+            Operator::Less => ordering.is_lt(),
+            Operator::LessOrEqual => ordering.is_le(),
+            Operator::Greater => ordering.is_gt(),
+            Operator::GreaterOrEqual => ordering.is_ge(),
+        }
+    }
+}
+
+fn compare_numbers(left: &str, right: &str) -> Option<Ordering> {
+    let left: f64 = left.parse().ok()?;
+    let right: f64 = right.parse().ok()?;
+    left.partial_cmp(&right)
+}
+
+/// The right side can also be a relative date like `7d` or `this-month`.
+fn compare_dates(left: &str, right: &str) -> Option<Ordering> {
+    let left = NaiveDate::parse_from_str(left, "%Y-%m-%d").ok()?;
+    let right = DateRange::parse(right, Local::now().date_naive()).ok()?;
+    Some(if left < right.start() {
+        Ordering::Less
+    } else if left > right.end() {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    })
+}
+
+/// How a query term is compared to the text of a tag.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// the tag has to be equal to the term
+    Exact,
+    /// the tag has to start with the term
+    Prefix,
+    /// the tag has to contain the term
+    #[default]
+    Substring,
+    /// the tag may differ from the term by a few typos
+    Fuzzy,
+}
+
+impl MatchMode {
+    pub fn matches(self, text: &str, term: &str) -> bool {
+        match self {
+            Self::Exact => text == term,
+            Self::Prefix => text.starts_with(term),
+            Self::Substring => text.contains(term),
+            Self::Fuzzy => edit_distance(text, term) <= fuzzy_tolerance(term),
+        }
+    }
+}
+
+/// The levenshtein distance between both texts counted in chars.
+pub fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    let mut current = vec![0; right.len() + 1];
+    for (left_index, left_char) in left.chars().enumerate() {
+        current[0] = left_index + 1;
+        for (right_index, right_char) in right.iter().enumerate() {
+            let substitution = previous[right_index] + usize::from(left_char != *right_char);
+            let insertion = current[right_index] + 1;
+            let deletion = previous[right_index + 1] + 1;
+            current[right_index + 1] = substitution.min(insertion).min(deletion);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[right.len()]
+}
+
+/// The number of typos allowed for the term.
+pub fn fuzzy_tolerance(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+fn expression(input: &str) -> IResult<&str, Query> {
+    let operator = delimited(multispace0, alt((keyword("OR"), tag("|"))), multispace0);
+    let (input, first) = conjunction(input)?;
+    let (input, rest) = many0(preceded(operator, conjunction))(input)?;
+    Ok((input, combine(first, rest, Query::Or)))
+}
+
+fn conjunction(input: &str) -> IResult<&str, Query> {
+    let operator = tuple((
+        multispace0,
+        opt(alt((keyword("AND"), tag("&")))),
+        multispace0,
+    ));
+    let (input, first) = negation(input)?;
+    let (input, rest) = many0(preceded(operator, negation))(input)?;
+    Ok((input, combine(first, rest, Query::And)))
+}
+
+fn negation(input: &str) -> IResult<&str, Query> {
+    let operator = pair(alt((keyword("NOT"), tag("!"))), multispace0);
+    alt((
+        map(preceded(operator, negation), |query| {
+            Query::Not(Box::new(query))
+        }),
+        atom,
+    ))(input)
+}
+
+fn atom(input: &str) -> IResult<&str, Query> {
+    let group = delimited(
+        pair(char('('), multispace0),
+        expression,
+        pair(multispace0, char(')')),
+    );
+    let phrase = delimited(char('"'), take_while(|char| char != '"'), char('"'));
+    let term = verify(word, |word: &str| !KEYWORDS.contains(&word));
+    alt((
+        group,
+        map(phrase, |text: &str| Query::Term(Term::Any(text.to_owned()))),
+        map(term, |word: &str| Query::Term(Term::parse(word))),
+    ))(input)
+}
+
+fn keyword<'a>(name: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    verify(word, move |word: &str| word == name)
+}
+
+fn word(input: &str) -> IResult<&str, &str> {
+    take_while1(|char: char| !char.is_whitespace() && !matches!(char, '(' | ')' | '"' | '&' | '|'))(
+        input,
+    )
+}
+
+fn combine(first: Query, rest: Vec<Query>, group: fn(Vec<Query>) -> Query) -> Query {
+    if rest.is_empty() {
+        return first;
+    }
+    let mut queries = vec![first];
+    queries.extend(rest);
+    group(queries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MatchMode, Query, Term};
+    use crate::tag::Tag;
+    use crate::vocabulary::Vocabulary;
+
+    fn any(word: &str) -> Query {
+        Query::Term(Term::Any(word.to_owned()))
+    }
+
+    fn not(query: Query) -> Query {
+        Query::Not(Box::new(query))
+    }
+
+    #[test]
+    fn and_binds_stronger_than_or() {
+        let expected = Query::Or(vec![any("a"), Query::And(vec![any("b"), any("c")])]);
+        assert_eq!(Query::parse("a OR b AND c").unwrap(), expected);
+        assert_eq!(Query::parse("a | b & c").unwrap(), expected);
+        assert_eq!(Query::parse("a OR b c").unwrap(), expected);
+    }
+
+    #[test]
+    fn not_binds_stronger_than_and() {
+        let expected = Query::And(vec![not(any("a")), any("b")]);
+        assert_eq!(Query::parse("NOT a b").unwrap(), expected);
+        assert_eq!(Query::parse("!a AND b").unwrap(), expected);
+        assert_eq!(Query::parse("NOT NOT a").unwrap(), not(not(any("a"))));
+    }
+
+    #[test]
+    fn parentheses_group_terms() {
+        assert_eq!(
+            Query::parse("(a OR b) c").unwrap(),
+            Query::And(vec![Query::Or(vec![any("a"), any("b")]), any("c")])
+        );
+        assert_eq!(
+            Query::parse("NOT (a | b)").unwrap(),
+            not(Query::Or(vec![any("a"), any("b")]))
+        );
+        assert_eq!(Query::parse(" ( ( a ) ) ").unwrap(), any("a"),);
+    }
+
+    #[test]
+    fn phrases_are_taken_literally() {
+        assert_eq!(
+            Query::parse("\"a OR b\" c").unwrap(),
+            Query::And(vec![any("a OR b"), any("c")])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        for input in ["(a", "a)", "a OR", "NOT", "\"a"] {
+            assert!(Query::parse(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn aliases_match_the_canonical_tag() {
+        let vocabulary = Vocabulary::with_tag_aliases("kubernetes: k8s kube");
+        let tags = [
+            Tag::Name("kubernetes".into()),
+            Tag::Name("kubernetes/helm".into()),
+        ];
+        for word in ["k8s", "#k8s", "kube/helm", "#k8s/helm"] {
+            let term = Term::parse_with(word, &vocabulary);
+            assert!(
+                tags.iter().any(|tag| term.matches(tag, MatchMode::Exact)),
+                "{}",
+                word
+            );
+        }
+        assert_eq!(
+            Term::parse_with("k8s", &vocabulary),
+            Term::Any("kubernetes".to_owned())
+        );
+        assert_eq!(
+            Term::parse_with("#k8s", &vocabulary),
+            Term::Tag(Tag::Name("kubernetes".into()))
+        );
+        assert_eq!(
+            Term::parse_with("docker", &vocabulary),
+            Term::Any("docker".to_owned())
+        );
+    }
+}
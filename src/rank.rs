@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::note::Note;
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+const TAG_BOOST: f64 = 2.0;
+
+/// Rank the notes by the relevance of their content for the given text.
+///
+/// The score is calculated using BM25. Terms that are also tags of the note
+/// are boosted. Notes without any matching term are left out.
+pub fn rank(notes: impl Iterator<Item = Note>, text: &str) -> Vec<(Note, f64)> {
+    let terms: Vec<String> = tokenize(text).unique().collect();
+    let documents: Vec<(Note, HashMap<String, usize>, usize)> = notes
+        .map(|note| {
+            let mut frequencies = HashMap::new();
+            let mut length = 0;
+            for token in tokenize(note.content()) {
+                *frequencies.entry(token).or_insert(0) += 1;
+                length += 1;
+            }
+            (note, frequencies, length)
+        })
+        .collect();
+
+    if documents.is_empty() {
+        return Vec::new();
+    }
+    let count = documents.len() as f64;
+    let average_length = documents
+        .iter()
+        .map(|(_, _, length)| *length as f64)
+        .sum::<f64>()
+        / count;
+    let inverse_frequencies: Vec<f64> = terms
+        .iter()
+        .map(|term| {
+            let frequency = documents
+                .iter()
+                .filter(|(_, frequencies, _)| frequencies.contains_key(term))
+                .count() as f64;
+            ((count - frequency + 0.5) / (frequency + 0.5) + 1.0).ln()
+        })
+        .collect();
+
+    documents
+        .into_iter()
+        .filter_map(|(note, frequencies, length)| {
+            let normalization = K1 * (1.0 - B + B * length as f64 / average_length.max(1.0));
+            let mut score = 0.0;
+            for (term, inverse_frequency) in terms.iter().zip(&inverse_frequencies) {
+                let frequency = frequencies.get(term).copied().unwrap_or_default() as f64;
+                score += inverse_frequency * frequency * (K1 + 1.0) / (frequency + normalization);
+                if note.has_tag(term) {
+                    score += TAG_BOOST * inverse_frequency;
+                }
+            }
+            (score > 0.0).then_some((note, score))
+        })
+        .sorted_by(|(_, left), (_, right)| right.total_cmp(left))
+        .collect()
+}
+
+/// The parts of the note containing any word of the text.
+pub fn matching_parts<'a>(note: &'a Note, text: &str) -> Vec<&'a str> {
+    let terms: Vec<String> = tokenize(text).collect();
+    note.parts()
+        .filter(|part| tokenize(part).any(|token| terms.contains(&token)))
+        .collect()
+}
+
+/// Split the text into lowercase words.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
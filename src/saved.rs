@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Named searches stored in the meta directory.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SavedSearches {
+    searches: BTreeMap<String, SavedSearch>,
+}
+
+/// The queries and the options of a search as given on the command line.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    #[serde(default)]
+    pub queries: Vec<String>,
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+impl SavedSearches {
+    pub fn load(config: &Config) -> eyre::Result<Self> {
+        let path = saved_searches_meta_file(config);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, config: &Config) -> eyre::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(saved_searches_meta_file(config), content)?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SavedSearch> {
+        self.searches.get(name)
+    }
+
+    pub fn insert(&mut self, name: String, search: SavedSearch) {
+        self.searches.insert(name, search);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &SavedSearch)> {
+        self.searches.iter()
+    }
+}
+
+impl SavedSearch {
+    /// Append the queries and options of the other search. The queries of
+    /// both searches are combined with `AND`.
+    pub fn extend(&mut self, other: SavedSearch) {
+        if !self.queries.is_empty() && !other.queries.is_empty() {
+            self.queries.insert(0, "(".to_owned());
+            self.queries.push(")".to_owned());
+        }
+        self.queries.extend(other.queries);
+        self.options.extend(other.options);
+    }
+}
+
+fn saved_searches_meta_file(config: &Config) -> PathBuf {
+    config.meta("searches.json")
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::note::Note;
+use crate::tag::Tag;
+
+/// How often a tag is used across the notes.
+#[derive(Debug, Clone, Serialize)]
+pub struct TagStats {
+    tag: String,
+    count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_used: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    co_occurring: Vec<CoOccurrence>,
+}
+
+/// A tag used together with another tag and the number of shared notes.
+#[derive(Debug, Clone, Serialize)]
+pub struct CoOccurrence {
+    tag: String,
+    count: usize,
+}
+
+#[derive(Default)]
+struct Usage {
+    count: usize,
+    last_used: Option<NaiveDate>,
+    co_occurring: HashMap<String, usize>,
+}
+
+impl TagStats {
+    /// Collect the usage of all tags, most used first. Tags are listed with
+    /// their prefix, so `#alice` and `@alice` are counted separately. For
+    /// every tag the top `co_occurring` tags appearing in the same notes are
+    /// kept.
+    pub fn collect<'a>(
+        notes: impl IntoIterator<Item = &'a Note>,
+        config: &Config,
+        co_occurring: usize,
+    ) -> Vec<Self> {
+        let mut usages: HashMap<String, Usage> = HashMap::new();
+        for note in notes {
+            let date = note.date(config);
+            let tags: Vec<String> = note.tags().iter().map(Tag::to_string).collect();
+            for tag in &tags {
+                let usage = usages.entry(tag.clone()).or_default();
+                usage.count += 1;
+                usage.last_used = usage.last_used.max(date);
+                if co_occurring == 0 {
+                    continue;
+                }
+                for other in tags.iter().filter(|other| *other != tag) {
+                    *usage.co_occurring.entry(other.clone()).or_insert(0) += 1;
+                }
+            }
+        }
+        usages
+            .into_iter()
+            .map(|(tag, usage)| Self {
+                tag,
+                count: usage.count,
+                last_used: usage.last_used,
+                co_occurring: usage
+                    .co_occurring
+                    .into_iter()
+                    .map(|(tag, count)| CoOccurrence { tag, count })
+                    .sorted_by(|left, right| {
+                        right.count.cmp(&left.count).then(left.tag.cmp(&right.tag))
+                    })
+                    .take(co_occurring)
+                    .collect(),
+            })
+            .sorted_by(|left, right| right.count.cmp(&left.count).then(left.tag.cmp(&right.tag)))
+            .collect()
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn last_used(&self) -> Option<NaiveDate> {
+        self.last_used
+    }
+
+    pub fn co_occurring(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.co_occurring
+            .iter()
+            .map(|co_occurrence| (co_occurrence.tag.as_str(), co_occurrence.count))
+    }
+}
@@ -1,21 +1,29 @@
 use std::borrow::Cow;
 use std::collections::HashSet;
 use std::fmt::Display;
+use std::iter::once;
 use std::ops::Not;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::person::Person;
+use crate::query::MatchMode;
+use crate::vocabulary::Vocabulary;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub const HIERARCHY_SEPARATOR: char = '/';
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Tag<'a> {
     Name(Cow<'a, str>),
+    /// A `#key=value` tag.
+    Attribute(Cow<'a, str>),
     Person(Person<'a>),
     Todo(TodoTag),
     Action(ActionTag),
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum TodoTag {
     Todo,
     Done,
@@ -24,9 +32,15 @@ pub enum TodoTag {
     Asap,
     Remind,
     Review,
+    /// A todo state defined in the config.
+    Custom {
+        name: String,
+        closed: bool,
+    },
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ActionTag {
     Load,
     Expand,
@@ -35,38 +49,66 @@ pub enum ActionTag {
 }
 
 impl<'a> Tag<'a> {
+    /// Parse all tags outside of code blocks and map them to their canonical
+    /// form.
     pub fn all(input: &'a str) -> HashSet<Self> {
+        let vocabulary = Vocabulary::get();
         let mut result = HashSet::new();
         for mut input in iter_non_code_blocks(input) {
             while !input.is_empty() {
                 match Self::parse_next(input) {
                     Ok((_, remaining, tag)) => {
                         input = remaining;
-                        result.insert(tag);
+                        result.insert(vocabulary.canonical(tag));
                     }
-                    Err(remaining) => input = remaining,
+                    Err((_, remaining)) => input = remaining,
                 }
             }
         }
         result
     }
 
-    pub fn parse_next(input: &'a str) -> Result<(&'a str, &'a str, Tag<'a>), &'a str> {
-        let Some(index) = input.find(|char| matches!(char, '#' | '@' | '~')) else {
-            return Err("");
+    /// Parse the next tag of the input. On success the text preceding the
+    /// tag, the remaining text and the tag are returned, otherwise the skipped
+    /// text and the remaining text.
+    ///
+    /// Tags have to start at a word boundary, so anchors in urls and e-mail
+    /// addresses are no tags. Urls and link targets are skipped as a whole.
+    pub fn parse_next(input: &'a str) -> Result<(&'a str, &'a str, Tag<'a>), (&'a str, &'a str)> {
+        let Some(index) = input.find(['#', '@', '~']) else {
+            return Err((input, ""));
         };
-        let (preceding, input) = input.split_at(index);
-        let (start, input) = input.split_at(1);
-        let (text, remaining) = input.split_once(
-            |char| matches!(char, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '+' | '=' | 'ä' | 'Ä' | 'ö' | 'Ö' | 'ü' | 'Ü' | 'ß' ).not()
-        )
-        .unwrap_or((input, ""));
-        if text.is_empty() {
-            return Err("");
+        let (preceding, input_from_start) = input.split_at(index);
+        if let Some(length) = skipped_link(preceding, input_from_start) {
+            return Err(input.split_at(index + length));
+        }
+        let skip = || Err(input.split_at(index + 1));
+        if !is_word_boundary(preceding.chars().next_back()) {
+            return skip();
+        }
+        let (start, input) = input_from_start.split_at(1);
+        let mut end = input.len();
+        let mut chars = input.char_indices().peekable();
+        while let Some((index, char)) = chars.next() {
+            let is_separator = start == "#"
+                && index > 0
+                && char == HIERARCHY_SEPARATOR
+                && chars.peek().is_some_and(|(_, next)| is_tag_char(*next));
+            if !is_tag_char(char) && !is_separator {
+                end = index;
+                break;
+            }
         }
+        let (text, remaining) = input.split_at(end);
+        // persons and actions followed by a path like `@scope/package`
+        let is_path = start != "#" && remaining.starts_with(['/', '\\']);
+        if text.is_empty() || is_path {
+            return skip();
+        }
+        let vocabulary = Vocabulary::get();
         let todo = |tag| Ok((preceding, remaining, Tag::Todo(tag)));
         let action = |tag| Ok((preceding, remaining, Tag::Action(tag)));
-        return match (start, text) {
+        match (start, text) {
             ("#", "todo") => todo(TodoTag::Todo),
             ("#", "done") => todo(TodoTag::Done),
             ("#", "idea") => todo(TodoTag::Idea),
@@ -74,19 +116,38 @@ impl<'a> Tag<'a> {
             ("#", "asap") => todo(TodoTag::Asap),
             ("#", "review") => todo(TodoTag::Review),
             ("#", "remind") => todo(TodoTag::Remind),
+            ("#", _) if vocabulary.todo_keyword(text).is_some() => {
+                todo(vocabulary.todo_keyword(text).unwrap().clone())
+            }
+            ("~", _) if vocabulary.action_keyword(text).is_some() => {
+                action(vocabulary.action_keyword(text).unwrap())
+            }
+            ("#", _) if is_attribute(text) => {
+                Ok((preceding, remaining, Tag::Attribute(text.into())))
+            }
             ("#", _) => Ok((preceding, remaining, Tag::Name(text.into()))),
             ("~", "load") => action(ActionTag::Load),
             ("~", "expand") => action(ActionTag::Expand),
             ("~", "steps") => action(ActionTag::Steps),
             ("~", "split") => action(ActionTag::Split),
             ("@", _) => Ok((preceding, remaining, Tag::Person(text.into()))),
-            _ => Err(input),
-        };
+            _ => skip(),
+        }
+    }
+
+    pub fn into_owned(self) -> Tag<'static> {
+        match self {
+            Tag::Name(value) => Tag::Name(Cow::Owned(value.into_owned())),
+            Tag::Attribute(value) => Tag::Attribute(Cow::Owned(value.into_owned())),
+            Tag::Person(value) => Tag::Person(Person::new(value.name().to_owned())),
+            Tag::Todo(value) => Tag::Todo(value),
+            Tag::Action(value) => Tag::Action(value),
+        }
     }
 
     pub fn prefix(&self) -> char {
         match self {
-            Self::Name(_) | Self::Todo(_) => '#',
+            Self::Name(_) | Self::Attribute(_) | Self::Todo(_) => '#',
             Self::Person(_) => '@',
             Self::Action(_) => '~',
         }
@@ -94,7 +155,7 @@ impl<'a> Tag<'a> {
 
     pub fn text(&self) -> &str {
         match self {
-            Tag::Name(value) => value,
+            Tag::Name(value) | Tag::Attribute(value) => value,
             Tag::Person(value) => value.name(),
             Tag::Todo(value) => value.text(),
             Tag::Action(value) => value.text(),
@@ -105,6 +166,35 @@ impl<'a> Tag<'a> {
         self.text().contains(other)
     }
 
+    /// Check the tag and all its parents against the query text.
+    pub fn matches(&self, other: &str, mode: MatchMode) -> bool {
+        self.ancestors()
+            .any(|ancestor| mode.matches(ancestor, other))
+    }
+
+    /// The segments of a hierarchical tag like `#project/billing/api`.
+    /// Only name tags can contain the separator.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.text().split(HIERARCHY_SEPARATOR)
+    }
+
+    /// The parents of a hierarchical tag followed by the tag itself, e.g.
+    /// `project`, `project/billing` and `project/billing/api`.
+    pub fn ancestors(&self) -> impl Iterator<Item = &str> {
+        let text = self.text();
+        text.match_indices(HIERARCHY_SEPARATOR)
+            .map(move |(index, _)| &text[..index])
+            .chain(once(text))
+    }
+
+    /// The key and the value of a `#key=value` tag.
+    pub fn attribute(&self) -> Option<(&str, &str)> {
+        match self {
+            Self::Attribute(value) => value.split_once('='),
+            _ => None,
+        }
+    }
+
     pub fn is_person(&self) -> bool {
         matches!(self, Self::Person(_))
     }
@@ -132,6 +222,16 @@ impl TodoTag {
             Self::Asap => "asap",
             Self::Remind => "remind",
             Self::Review => "review",
+            Self::Custom { name, .. } => name,
+        }
+    }
+
+    /// Check if the todo is finished.
+    pub fn is_closed(&self) -> bool {
+        match self {
+            Self::Done => true,
+            Self::Custom { closed, .. } => *closed,
+            _ => false,
         }
     }
 }
@@ -147,7 +247,47 @@ impl ActionTag {
     }
 }
 
-fn iter_non_code_blocks(input: &str) -> impl Iterator<Item = &str> {
+/// Letters and numbers of any script and `_`, `-`, `+` and `=` can be used in
+/// tags.
+pub fn is_tag_char(char: char) -> bool {
+    char.is_alphanumeric() || matches!(char, '_' | '-' | '+' | '=')
+}
+
+/// Tags have to start at the beginning of a word. Words can also start after
+/// punctuation like parentheses or quotes, but not inside of paths, urls or
+/// e-mail addresses.
+fn is_word_boundary(previous: Option<char>) -> bool {
+    previous.is_none_or(|char| {
+        !is_tag_char(char)
+            && !matches!(
+                char,
+                '/' | '\\' | '.' | ':' | '#' | '@' | '~' | '&' | '%' | '$'
+            )
+    })
+}
+
+/// The length of the url or the markdown link target starting at or
+/// containing the sigil at the start of the input.
+fn skipped_link(preceding: &str, input: &str) -> Option<usize> {
+    let word = preceding
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or(preceding);
+    let word_end = input.find(char::is_whitespace).unwrap_or(input.len());
+    if word.ends_with("](") {
+        return Some(input[..word_end].find(')').unwrap_or(word_end));
+    }
+    let word = word.trim_start_matches(['<', '(', '[']);
+    let is_url = word.contains("://") || word.starts_with("www.") || word.starts_with("mailto:");
+    is_url.then_some(word_end)
+}
+
+fn is_attribute(text: &str) -> bool {
+    text.split_once('=')
+        .is_some_and(|(key, value)| !key.is_empty() && !value.is_empty())
+}
+
+pub fn iter_non_code_blocks(input: &str) -> impl Iterator<Item = &str> {
     let mut in_code_block = false;
     input
         .split("```")
@@ -163,3 +303,121 @@ fn iter_non_code_blocks(input: &str) -> impl Iterator<Item = &str> {
             })
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::Tag;
+    use crate::query::MatchMode;
+
+    /// Inputs and the tags found in them.
+    const CORPUS: &[(&str, &[&str])] = &[
+        ("#rust and @alice", &["#rust", "@alice"]),
+        (
+            "(#paren) \"#quoted\" *#bold*",
+            &["#bold", "#paren", "#quoted"],
+        ),
+        ("end of a sentence with #tag.", &["#tag"]),
+        ("#project/billing/api", &["#project/billing/api"]),
+        ("#todo #done ~load", &["#done", "#todo", "~load"]),
+        ("#priority=high", &["#priority=high"]),
+        ("#café @józef #日本", &["#café", "#日本", "@józef"]),
+        // urls
+        ("https://example.org/page#section", &[]),
+        ("see https://example.org/#/route #tag", &["#tag"]),
+        ("<https://example.org/a#b>", &[]),
+        ("www.example.org/@user", &[]),
+        ("path/#x and issue#12", &[]),
+        // e-mail addresses
+        ("bob@example.com", &[]),
+        ("mail <bob@example.com> or ask @bob", &["@bob"]),
+        ("mailto:bob@example.com", &[]),
+        // headings
+        ("# Heading\n## Sub heading\n#tag", &["#tag"]),
+        ("C# is fun", &[]),
+        // link targets
+        ("[docs](#anchor) #real", &["#real"]),
+        ("[docs](https://example.org/#a) and [home](~/notes)", &[]),
+        ("[see @bob](@bob)", &["@bob"]),
+        // paths
+        ("cd ~/notes", &[]),
+        ("cp ~load/file .", &[]),
+        ("npm install @scope/package", &[]),
+        // code
+        ("text `#code` ```\n#block\n``` #after", &["#after"]),
+    ];
+
+    #[test]
+    fn corpus() {
+        for (input, expected) in CORPUS {
+            let tags = Tag::all(input)
+                .iter()
+                .map(|tag| tag.to_string())
+                .sorted()
+                .collect_vec();
+            assert_eq!(tags, *expected, "tags of {:?}", input);
+        }
+    }
+
+    #[test]
+    fn keeps_text_without_tags() {
+        assert_eq!(Tag::parse_next("no tags"), Err(("no tags", "")));
+        assert_eq!(Tag::parse_next("a@b c"), Err(("a@", "b c")));
+        let (preceding, remaining, tag) = Tag::parse_next("see #x, then").unwrap();
+        assert_eq!((preceding, remaining), ("see ", ", then"));
+        assert_eq!(tag, Tag::Name("x".into()));
+    }
+
+    #[test]
+    fn nested_tags_match_their_ancestors() {
+        let tag = Tag::Name("project/billing/api".into());
+        let cases = [
+            (
+                MatchMode::Exact,
+                &["project", "project/billing", "project/billing/api"][..],
+            ),
+            (
+                MatchMode::Prefix,
+                &["proj", "project/bill", "project/billing/a"],
+            ),
+            (MatchMode::Substring, &["billing", "ject/bil", "api"]),
+            (
+                MatchMode::Fuzzy,
+                &["projet", "project/biling", "project/biling/apu"],
+            ),
+        ];
+        for (mode, terms) in cases {
+            for term in terms {
+                assert!(tag.matches(term, mode), "{:?} {}", mode, term);
+            }
+        }
+        let misses = [
+            (MatchMode::Exact, &["billing", "project/bill", "api"][..]),
+            (MatchMode::Prefix, &["billing", "roject"]),
+            (MatchMode::Substring, &["web", "project/api"]),
+            (MatchMode::Fuzzy, &["billing", "prjoetc"]),
+        ];
+        for (mode, terms) in misses {
+            for term in terms {
+                assert!(!tag.matches(term, mode), "{:?} {}", mode, term);
+            }
+        }
+    }
+
+    #[test]
+    fn lists_the_ancestors() {
+        let tag = Tag::Name("a/b/c".into());
+        assert_eq!(tag.ancestors().collect_vec(), ["a", "a/b", "a/b/c"]);
+        assert_eq!(tag.segments().collect_vec(), ["a", "b", "c"]);
+        for mode in [
+            MatchMode::Exact,
+            MatchMode::Prefix,
+            MatchMode::Substring,
+            MatchMode::Fuzzy,
+        ] {
+            assert!(tag.matches("a", mode), "{:?}", mode);
+            assert!(tag.matches("a/b", mode), "{:?}", mode);
+        }
+    }
+}
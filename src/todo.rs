@@ -3,8 +3,6 @@ use std::collections::HashSet;
 use crate::note::Note;
 use crate::tag::Tag;
 
-use super::tag::TodoTag;
-
 pub struct Todo<'a> {
     content: &'a str,
     tags: HashSet<Tag<'a>>,
@@ -12,7 +10,9 @@ pub struct Todo<'a> {
 
 impl<'a> Todo<'a> {
     pub fn all(note: &'a Note) -> impl Iterator<Item = Self> {
-        note.parts().filter_map(|part| Todo::parse_str(part))
+        note.todo_parts()
+            .iter()
+            .filter_map(|part| Todo::parse_str(part))
     }
 
     pub fn parse_str(content: &'a str) -> Option<Self> {
@@ -26,9 +26,10 @@ impl<'a> Todo<'a> {
         &self.content
     }
 
+    /// Check if the todo is marked as done or with a closed custom state.
     pub fn is_done(&self) -> bool {
         self.tags
             .iter()
-            .any(|tag| matches!(tag, Tag::Todo(TodoTag::Done)))
+            .any(|tag| matches!(tag, Tag::Todo(todo) if todo.is_closed()))
     }
 }
@@ -0,0 +1,103 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::note::Note;
+use crate::tag::{Tag, HIERARCHY_SEPARATOR};
+
+/// The hierarchy of the name tags with the number of notes for every tag.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct TagTree {
+    count: usize,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    children: BTreeMap<String, TagTree>,
+}
+
+impl TagTree {
+    pub fn build<'a>(notes: impl IntoIterator<Item = &'a Note>) -> Self {
+        let mut tree = Self::default();
+        for note in notes {
+            tree.count += 1;
+            let paths: HashSet<&str> = note
+                .tags()
+                .iter()
+                .filter(|tag| matches!(tag, Tag::Name(_)))
+                .flat_map(|tag| tag.ancestors())
+                .collect();
+            for path in paths {
+                tree.insert(path.split(HIERARCHY_SEPARATOR));
+            }
+        }
+        tree
+    }
+
+    /// The number of notes containing the tag or one of its children.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = (&String, &TagTree)> {
+        self.children.iter()
+    }
+}
+
+impl TagTree {
+    fn insert<'a>(&mut self, mut segments: impl Iterator<Item = &'a str>) {
+        let Some(segment) = segments.next() else {
+            self.count += 1;
+            return;
+        };
+        self.children
+            .entry(segment.to_owned())
+            .or_default()
+            .insert(segments);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::TagTree;
+    use crate::note::Note;
+    use crate::tag::Tag;
+
+    fn note(tags: &[&str]) -> Note {
+        let tags = tags
+            .iter()
+            .map(|tag| Tag::Name(tag.to_string().into()))
+            .collect();
+        Note::cached(PathBuf::from("note.md"), tags, Vec::new(), None)
+    }
+
+    fn child<'a>(tree: &'a TagTree, name: &str) -> &'a TagTree {
+        tree.children()
+            .find(|(child, _)| *child == name)
+            .map(|(_, tree)| tree)
+            .unwrap()
+    }
+
+    #[test]
+    fn counts_notes_for_every_ancestor() {
+        let notes = [note(&["a/b/c", "a/b"]), note(&["a/d"]), note(&["e"])];
+        let tree = TagTree::build(&notes);
+        assert_eq!(tree.count(), 3);
+        assert_eq!(tree.children().count(), 2);
+        let a = child(&tree, "a");
+        assert_eq!(a.count(), 2);
+        assert_eq!(child(a, "b").count(), 1);
+        assert_eq!(child(child(a, "b"), "c").count(), 1);
+        assert_eq!(child(a, "d").count(), 1);
+        assert_eq!(child(&tree, "e").count(), 1);
+        assert_eq!(child(&tree, "e").children().count(), 0);
+    }
+
+    #[test]
+    fn skips_other_tags() {
+        let tags = [Tag::Person("alice".into()), Tag::Attribute("a=b".into())];
+        let note = Note::cached(PathBuf::new(), tags.into_iter().collect(), Vec::new(), None);
+        let tree = TagTree::build([&note]);
+        assert_eq!(tree.count(), 1);
+        assert_eq!(tree.children().count(), 0);
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt::Write;
+
+use crate::person::{Profile, Profiles};
+
+/// Write the profiles as vCards. The handle of every person is the first
+/// nickname followed by the aliases.
+pub fn export(profiles: &Profiles) -> String {
+    let mut content = String::new();
+    for (handle, profile) in profiles.iter() {
+        let name = profile.name.as_deref().unwrap_or(handle);
+        let (given, family) = match name.rsplit_once(' ') {
+            Some((given, family)) => (given, family),
+            None => (name, ""),
+        };
+        let nicknames: Vec<String> = [handle]
+            .into_iter()
+            .chain(&profile.aliases)
+            .map(|nickname| escape(nickname.trim_start_matches('@')))
+            .collect();
+        content.push_str("BEGIN:VCARD\r\nVERSION:3.0\r\n");
+        write!(content, "FN:{}\r\n", escape(name)).unwrap();
+        write!(content, "N:{};{};;;\r\n", escape(family), escape(given)).unwrap();
+        write!(content, "NICKNAME:{}\r\n", nicknames.join(",")).unwrap();
+        if let Some(email) = &profile.email {
+            write!(content, "EMAIL:{}\r\n", escape(email)).unwrap();
+        }
+        if let Some(team) = &profile.team {
+            write!(content, "ORG:{}\r\n", escape(team)).unwrap();
+        }
+        if let Some(notes) = &profile.notes {
+            write!(content, "NOTE:{}\r\n", escape(notes)).unwrap();
+        }
+        content.push_str("END:VCARD\r\n");
+    }
+    content
+}
+
+/// Read the profiles of the vCards. The handle is the first nickname or is
+/// derived from the name of the person.
+pub fn import(content: &str) -> Vec<(String, Profile)> {
+    let mut cards = Vec::new();
+    let mut card: Option<(Vec<String>, Profile)> = None;
+    for line in unfold(content) {
+        let Some((property, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = property
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .to_uppercase();
+        match (name.as_str(), &mut card) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("VCARD") => {
+                card = Some((Vec::new(), Profile::default()));
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VCARD") => {
+                let (nicknames, profile) = card.take().unwrap();
+                cards.extend(into_person(nicknames, profile));
+            }
+            ("FN", Some((_, profile))) => profile.name = non_empty(unescape(value)),
+            ("NICKNAME", Some((nicknames, _))) => {
+                nicknames.extend(split(value, ',').filter_map(|nickname| handle(&nickname)));
+            }
+            ("EMAIL", Some((_, profile))) if profile.email.is_none() => {
+                profile.email = non_empty(unescape(value));
+            }
+            ("ORG", Some((_, profile))) => {
+                profile.team = split(value, ';').next().and_then(non_empty);
+            }
+            ("NOTE", Some((_, profile))) => profile.notes = non_empty(unescape(value)),
+            _ => {}
+        }
+    }
+    cards
+}
+
+fn into_person(nicknames: Vec<String>, mut profile: Profile) -> Option<(String, Profile)> {
+    let mut nicknames = nicknames.into_iter();
+    let handle = nicknames
+        .next()
+        .or_else(|| handle(profile.name.as_deref()?))?;
+    profile.aliases = nicknames.filter(|alias| *alias != handle).collect();
+    Some((handle, profile))
+}
+
+/// A handle like `alice-smith` derived from a name.
+fn handle(name: &str) -> Option<String> {
+    let handle = name
+        .trim_start_matches('@')
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|char| char.is_alphanumeric() || matches!(char, '-' | '_'))
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+    non_empty(handle)
+}
+
+/// Join the lines continued with a space or tab.
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    lines
+}
+
+/// Split the value at the separators, which are not escaped.
+fn split(value: &str, separator: char) -> impl Iterator<Item = String> + '_ {
+    let mut escaped = false;
+    value
+        .split(move |char| {
+            let split = char == separator && !escaped;
+            escaped = char == '\\' && !escaped;
+            split
+        })
+        .map(unescape)
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        match (char, chars.clone().next()) {
+            ('\\', Some('n' | 'N')) => {
+                chars.next();
+                result.push('\n');
+            }
+            ('\\', Some(next)) => {
+                chars.next();
+                result.push(next);
+            }
+            (char, _) => result.push(char),
+        }
+    }
+    result
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim().to_owned();
+    (!value.is_empty()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export, import};
+    use crate::person::{Profile, Profiles};
+
+    #[test]
+    fn reads_escaped_values() {
+        let content = "BEGIN:VCARD\r\nFN:Smith\\, Alice\r\nNICKNAME:alice,a\\,smith\r\n\
+            ORG:R\\;D;Platform\r\nNOTE:first\\nsecond\r\nEND:VCARD\r\n";
+        let persons = import(content);
+        assert_eq!(persons.len(), 1);
+        let (handle, profile) = &persons[0];
+        assert_eq!(handle, "alice");
+        assert_eq!(profile.name.as_deref(), Some("Smith, Alice"));
+        assert_eq!(profile.aliases, ["asmith"]);
+        assert_eq!(profile.team.as_deref(), Some("R;D"));
+        assert_eq!(profile.notes.as_deref(), Some("first\nsecond"));
+    }
+
+    #[test]
+    fn unfolds_continued_lines() {
+        let content =
+            "begin:vcard\nFN:Alice\n  Smith\nEMAIL;TYPE=work:alice@\n\texample.com\nend:vcard\n";
+        let persons = import(content);
+        assert_eq!(persons.len(), 1);
+        let (handle, profile) = &persons[0];
+        assert_eq!(handle, "alice-smith");
+        assert_eq!(profile.name.as_deref(), Some("Alice Smith"));
+        assert_eq!(profile.email.as_deref(), Some("alice@example.com"));
+    }
+
+    #[test]
+    fn skips_cards_without_a_name() {
+        assert!(import("BEGIN:VCARD\nEMAIL:a@example.com\nEND:VCARD\n").is_empty());
+        assert!(import("FN:Alice\n").is_empty());
+    }
+
+    #[test]
+    fn round_trips_the_profiles() {
+        let alice = Profile {
+            name: Some("Alice Smith".to_owned()),
+            aliases: vec!["ali".to_owned(), "a-smith".to_owned()],
+            email: Some("alice@example.com".to_owned()),
+            team: Some("R;D".to_owned()),
+            notes: Some("Prefers async updates.\nLikes #rust.".to_owned()),
+        };
+        let mut profiles = Profiles::default();
+        profiles.update("alice", alice.clone());
+        profiles.update("bob", Profile::default());
+
+        let persons = import(&export(&profiles));
+        let bob = Profile {
+            name: Some("bob".to_owned()),
+            ..Profile::default()
+        };
+        assert_eq!(
+            persons,
+            [("alice".to_owned(), alice), ("bob".to_owned(), bob)]
+        );
+    }
+}
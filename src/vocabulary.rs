@@ -0,0 +1,181 @@
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use eyre::eyre;
+
+use crate::config::Config;
+use crate::config::TodoState;
+use crate::person::{Person, Profiles};
+use crate::tag::{ActionTag, Tag, TodoTag};
+
+static VOCABULARY: OnceLock<Vocabulary> = OnceLock::new();
+
+/// The words used in the notes, which are mapped while parsing tags.
+///
+/// Tag aliases are read from the `aliases.txt` file in the meta directory.
+/// Every line maps the synonyms after the colon to the canonical tag before
+/// it:
+///
+/// ```text
+/// kubernetes: k8s kube
+/// ```
+///
+/// Additional keywords for todo and action tags and custom todo states are
+/// read from the config. The aliases of persons are read from their profiles.
+#[derive(Debug, Default)]
+pub struct Vocabulary {
+    tag_aliases: HashMap<String, String>,
+    person_aliases: HashMap<String, String>,
+    todo_keywords: HashMap<String, TodoTag>,
+    action_keywords: HashMap<String, ActionTag>,
+}
+
+impl Vocabulary {
+    /// Load the vocabulary once for the whole run of the application. Fails
+    /// if the vocabulary was already installed or used before, because tags
+    /// parsed until then would miss the aliases.
+    pub fn install(config: &Config) -> eyre::Result<()> {
+        VOCABULARY
+            .set(Self::load(config))
+            .map_err(|_| eyre!("the vocabulary was used before it was installed"))
+    }
+
+    /// The installed vocabulary. Without an installed vocabulary an empty one
+    /// is used for the rest of the run.
+    pub fn get() -> &'static Self {
+        VOCABULARY.get_or_init(Self::default)
+    }
+
+    pub fn load(config: &Config) -> Self {
+        let tag_aliases = fs::read_to_string(tag_aliases_meta_file(config))
+            .map(|content| parse_aliases(&content))
+            .unwrap_or_default();
+        let keywords = config.keywords();
+        let mut todo_keywords: HashMap<String, TodoTag> = keywords
+            .states
+            .iter()
+            .map(|(name, state)| {
+                let tag = TodoTag::Custom {
+                    name: name.clone(),
+                    closed: *state == TodoState::Closed,
+                };
+                (name.clone(), tag)
+            })
+            .collect();
+        todo_keywords.extend(keywords.todo.clone());
+        let action_keywords = keywords.action.clone().into_iter().collect();
+        let person_aliases = Profiles::load(config)
+            .map(|profiles| person_aliases(&profiles))
+            .unwrap_or_default();
+        Self {
+            tag_aliases,
+            person_aliases,
+            todo_keywords,
+            action_keywords,
+        }
+    }
+
+    /// A vocabulary with only the tag aliases of an `aliases.txt` file.
+    #[cfg(test)]
+    pub fn with_tag_aliases(content: &str) -> Self {
+        Self {
+            tag_aliases: parse_aliases(content),
+            ..Self::default()
+        }
+    }
+
+    /// The todo tag for a keyword defined in the config.
+    pub fn todo_keyword(&self, text: &str) -> Option<&TodoTag> {
+        self.todo_keywords.get(text)
+    }
+
+    /// The action tag for a keyword defined in the config.
+    pub fn action_keyword(&self, text: &str) -> Option<ActionTag> {
+        self.action_keywords.get(text).copied()
+    }
+
+    /// Replace the tag with its canonical form. The parents of hierarchical
+    /// tags are replaced as well. Persons are replaced by their handle.
+    pub fn canonical<'a>(&self, tag: Tag<'a>) -> Tag<'a> {
+        if let Tag::Person(person) = &tag {
+            return match self.person_aliases.get(&person.name().to_lowercase()) {
+                Some(handle) => Tag::Person(Person::new(handle.clone())),
+                None => tag,
+            };
+        }
+        let Tag::Name(name) = &tag else {
+            return tag;
+        };
+        let canonical = tag
+            .ancestors()
+            .filter_map(|ancestor| Some((ancestor, self.tag_aliases.get(ancestor)?)))
+            .last()
+            .map(|(ancestor, canonical)| format!("{}{}", canonical, &name[ancestor.len()..]));
+        match canonical {
+            Some(canonical) => Tag::Name(Cow::Owned(canonical)),
+            None => tag,
+        }
+    }
+
+    /// A hash of the vocabulary used to detect changes.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.tag_aliases
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .hash(&mut hasher);
+        self.person_aliases
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .hash(&mut hasher);
+        self.todo_keywords
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .hash(&mut hasher);
+        self.action_keywords
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+fn parse_aliases(content: &str) -> HashMap<String, String> {
+    let mut aliases = HashMap::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((canonical, synonyms)) = line.split_once(':') else {
+            continue;
+        };
+        let canonical = canonical.trim().trim_start_matches('#');
+        for synonym in synonyms.split(|char: char| char.is_whitespace() || char == ',') {
+            let synonym = synonym.trim_start_matches('#');
+            if !synonym.is_empty() && synonym != canonical {
+                aliases.insert(synonym.to_owned(), canonical.to_owned());
+            }
+        }
+    }
+    aliases
+}
+
+fn person_aliases(profiles: &Profiles) -> HashMap<String, String> {
+    let mut aliases = HashMap::new();
+    for (handle, profile) in profiles.iter() {
+        for alias in &profile.aliases {
+            let alias = alias.trim_start_matches('@').to_lowercase();
+            aliases.insert(alias, handle.clone());
+        }
+    }
+    aliases
+}
+
+fn tag_aliases_meta_file(config: &Config) -> PathBuf {
+    config.meta("aliases.txt")
+}